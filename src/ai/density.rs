@@ -0,0 +1,124 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use crate::ai::hunt::targets;
+use crate::ai::{cell, cells, pick, Strategy};
+use crate::game::{Direction, EnemyBoardCell};
+use crate::location::Location;
+use crate::ship::ShipType;
+
+/// Counts, for every cell, the ways the ships still afloat could be placed over it without
+/// crossing a miss or a sunk ship, and fires at the cell with the most. Once something has been
+/// hit only placements through the hits count, weighted by how many hits they explain.
+pub struct Density {
+    rng: StdRng,
+}
+
+impl Density {
+    pub fn new(seed: u64) -> Self {
+        Density { rng: StdRng::seed_from_u64(seed) }
+    }
+}
+
+impl Strategy for Density {
+    fn next_shot(&mut self, board: &[Vec<EnemyBoardCell>], remaining: &[ShipType]) -> Option<Location> {
+        let scores = scores(board, remaining);
+        let best = scores.iter().flatten().copied().max().unwrap_or(0);
+        if best == 0 {
+            // nothing left fits what the board shows, fall back to the cells around the hits
+            if let Some(target) = pick(&mut self.rng, &targets(board)) {
+                return Some(target);
+            }
+            return pick(&mut self.rng, &cells(board, EnemyBoardCell::Empty));
+        }
+        let mut candidates = Vec::new();
+        for (row, cells) in scores.iter().enumerate() {
+            for (col, score) in cells.iter().enumerate() {
+                if *score == best {
+                    candidates.push(Location { row: row as u32, col: col as u32 });
+                }
+            }
+        }
+        pick(&mut self.rng, &candidates)
+    }
+}
+
+// how many placements of the remaining ships cover each empty cell, indexed `[row][col]`
+fn scores(board: &[Vec<EnemyBoardCell>], remaining: &[ShipType]) -> Vec<Vec<u32>> {
+    let height = board.len();
+    let width = board.first().map_or(0, |row| row.len());
+    let mut scores = vec![vec![0; width]; height];
+    let hunting = cells(board, EnemyBoardCell::Hit).is_empty();
+    for class in remaining {
+        for direction in [Direction::Up, Direction::Down, Direction::Left, Direction::Right] {
+            let footprint = class.footprint(direction);
+            for row in 0..height as i64 {
+                for col in 0..width as i64 {
+                    let covered: Option<Vec<(i64, i64)>> = footprint.iter()
+                        .map(|(row_offset, col_offset)| (row + row_offset, col + col_offset))
+                        .map(|(row, col)| match cell(board, row, col) {
+                            Some(EnemyBoardCell::Empty) | Some(EnemyBoardCell::Hit) => Some((row, col)),
+                            _ => None,
+                        })
+                        .collect();
+                    let covered = match covered {
+                        Some(covered) => covered,
+                        None => continue,
+                    };
+                    let hits = covered.iter().filter(|(row, col)| cell(board, *row, *col) == Some(EnemyBoardCell::Hit)).count() as u32;
+                    if !hunting && hits == 0 {
+                        continue;
+                    }
+                    let weight = if hunting { 1 } else { hits * hits };
+                    for (row, col) in covered {
+                        if cell(board, row, col) == Some(EnemyBoardCell::Empty) {
+                            scores[row as usize][col as usize] += weight;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    scores
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ai::{Density, Strategy};
+    use crate::fleet::FleetSpec;
+    use crate::game::EnemyBoardCell;
+    use crate::location::Location;
+    use crate::ship::ShipType;
+
+    fn fleet() -> Vec<ShipType> {
+        FleetSpec::original().build().into_iter().map(|ship| ship.class).collect()
+    }
+
+    #[test]
+    fn aims_for_the_middle_of_an_empty_board() {
+        let board = vec![vec![EnemyBoardCell::Empty; 10]; 10];
+        let middle = [Location { row: 4, col: 4 }, Location { row: 4, col: 5 }, Location { row: 5, col: 4 }, Location { row: 5, col: 5 }];
+        for seed in 0..10 {
+            let shot = Density::new(seed).next_shot(&board, &fleet()).unwrap();
+            assert!(middle.contains(&shot), "{:?}", shot);
+        }
+    }
+
+    #[test]
+    fn avoids_gaps_too_small_for_any_ship() {
+        // a single empty cell walled in by misses can't hold anything that is left
+        let mut board = vec![vec![EnemyBoardCell::Miss; 5]; 5];
+        board[0][0] = EnemyBoardCell::Empty;
+        board[4][2] = EnemyBoardCell::Empty;
+        board[4][3] = EnemyBoardCell::Empty;
+        assert_eq!(Density::new(0).next_shot(&board, &[ShipType::Destroyer]).map(|l| l.row), Some(4));
+    }
+
+    #[test]
+    fn finishes_off_a_hit_ship() {
+        let mut board = vec![vec![EnemyBoardCell::Empty; 10]; 10];
+        board[2][3] = EnemyBoardCell::Hit;
+        board[2][4] = EnemyBoardCell::Hit;
+        let shot = Density::new(0).next_shot(&board, &fleet()).unwrap();
+        assert!([Location { row: 2, col: 2 }, Location { row: 2, col: 5 }].contains(&shot), "{:?}", shot);
+    }
+}
@@ -0,0 +1,106 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use crate::ai::{cell, cells, pick, Strategy};
+use crate::game::EnemyBoardCell;
+use crate::location::Location;
+use crate::ship::ShipType;
+
+/// Fires at random until it hits a ship, then works along the rows and columns around the hit
+/// until the ship is sunk. With parity it only hunts on a checkerboard spaced by the smallest
+/// ship still afloat, since every ship has to cover one of those cells.
+pub struct HuntTarget {
+    rng: StdRng,
+    parity: bool,
+}
+
+impl HuntTarget {
+    pub fn new(seed: u64) -> Self {
+        HuntTarget { rng: StdRng::seed_from_u64(seed), parity: false }
+    }
+
+    pub fn with_parity(seed: u64) -> Self {
+        HuntTarget { rng: StdRng::seed_from_u64(seed), parity: true }
+    }
+}
+
+impl Strategy for HuntTarget {
+    fn next_shot(&mut self, board: &[Vec<EnemyBoardCell>], remaining: &[ShipType]) -> Option<Location> {
+        if let Some(target) = pick(&mut self.rng, &targets(board)) {
+            return Some(target);
+        }
+        let empty = cells(board, EnemyBoardCell::Empty);
+        if self.parity {
+            let spacing = remaining.iter().map(|class| class.size()).min().unwrap_or(1).max(1);
+            let checkerboard: Vec<Location> = empty.iter().filter(|l| (l.row + l.col) % spacing == 0).copied().collect();
+            if let Some(location) = pick(&mut self.rng, &checkerboard) {
+                return Some(location);
+            }
+        }
+        pick(&mut self.rng, &empty)
+    }
+}
+
+/// The empty cells next to hits on ships that aren't sunk yet. When hits line up, only the
+/// cells carrying on the line are returned.
+pub(super) fn targets(board: &[Vec<EnemyBoardCell>]) -> Vec<Location> {
+    let mut lined = Vec::new();
+    let mut adjacent = Vec::new();
+    for hit in cells(board, EnemyBoardCell::Hit) {
+        let (row, col) = (hit.row as i64, hit.col as i64);
+        for (row_step, col_step) in [(0, 1), (1, 0), (0, -1), (-1, 0)] {
+            if cell(board, row + row_step, col + col_step) != Some(EnemyBoardCell::Empty) {
+                continue;
+            }
+            let target = Location { row: (row + row_step) as u32, col: (col + col_step) as u32 };
+            if cell(board, row - row_step, col - col_step) == Some(EnemyBoardCell::Hit) {
+                lined.push(target);
+            } else {
+                adjacent.push(target);
+            }
+        }
+    }
+    if lined.is_empty() { adjacent } else { lined }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ai::{HuntTarget, Strategy};
+    use crate::game::EnemyBoardCell;
+    use crate::location::Location;
+    use crate::ship::ShipType;
+
+    #[test]
+    fn targets_the_cells_around_a_hit() {
+        let mut board = vec![vec![EnemyBoardCell::Empty; 10]; 10];
+        board[5][5] = EnemyBoardCell::Hit;
+        board[4][5] = EnemyBoardCell::Miss;
+        let around = [Location { row: 6, col: 5 }, Location { row: 5, col: 4 }, Location { row: 5, col: 6 }];
+        for seed in 0..10 {
+            let shot = HuntTarget::new(seed).next_shot(&board, &[ShipType::Cruiser]).unwrap();
+            assert!(around.contains(&shot), "{:?}", shot);
+        }
+    }
+
+    #[test]
+    fn follows_a_line_of_hits() {
+        let mut board = vec![vec![EnemyBoardCell::Empty; 10]; 10];
+        board[5][5] = EnemyBoardCell::Hit;
+        board[5][6] = EnemyBoardCell::Hit;
+        board[5][7] = EnemyBoardCell::Miss;
+        for seed in 0..10 {
+            assert_eq!(HuntTarget::new(seed).next_shot(&board, &[ShipType::Cruiser]), Some(Location { row: 5, col: 4 }));
+        }
+    }
+
+    #[test]
+    fn parity_hunts_on_a_checkerboard() {
+        let board = vec![vec![EnemyBoardCell::Empty; 10]; 10];
+        let mut strategy = HuntTarget::with_parity(7);
+        for _ in 0..20 {
+            let shot = strategy.next_shot(&board, &[ShipType::Carrier, ShipType::Destroyer]).unwrap();
+            assert_eq!((shot.row + shot.col) % 2, 0);
+            let shot = strategy.next_shot(&board, &[ShipType::Carrier, ShipType::Cruiser]).unwrap();
+            assert_eq!((shot.row + shot.col) % 3, 0);
+        }
+    }
+}
@@ -0,0 +1,233 @@
+//! Computer opponents: strategies that pick the next shot from what a player can see of the enemy board.
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+use crate::game::{EnemyBoardCell, GameState};
+use crate::location::Location;
+use crate::player::PlayerType;
+use crate::ship::ShipType;
+
+mod density;
+mod hunt;
+mod random;
+
+pub use density::Density;
+pub use hunt::HuntTarget;
+pub use random::RandomShots;
+
+/// The names `strategy` accepts, from weakest to strongest.
+pub const STRATEGIES: [&str; 4] = ["random", "hunt", "parity", "density"];
+
+/// Chooses where to fire next.
+pub trait Strategy {
+    /// Picks a cell of `board` that hasn't been fired at, or `None` if there are none left.
+    /// `board` is the enemy board as seen by the player, indexed `[row][col]`, and `remaining`
+    /// holds the classes of the enemy ships that are still afloat.
+    fn next_shot(&mut self, board: &[Vec<EnemyBoardCell>], remaining: &[ShipType]) -> Option<Location>;
+}
+
+/// Builds one of the `STRATEGIES` by name, with its random choices seeded by `seed`.
+pub fn strategy(name: &str, seed: u64) -> Option<Box<dyn Strategy + Send>> {
+    match name.to_lowercase().as_str() {
+        "random" => Some(Box::new(RandomShots::new(seed))),
+        "hunt" => Some(Box::new(HuntTarget::new(seed))),
+        "parity" => Some(Box::new(HuntTarget::with_parity(seed))),
+        "density" => Some(Box::new(Density::new(seed))),
+        _ => None,
+    }
+}
+
+/// Asks `strategy` where `player` should fire next in `game`.
+pub fn next_shot(strategy: &mut dyn Strategy, game: &GameState, player: PlayerType) -> Option<Location> {
+    strategy.next_shot(&known_board(game, player), &remaining(game, player))
+}
+
+/// Asks `strategy` for all of `player`'s shots this turn, see `GameState::shots_this_turn`.
+/// Cells already picked for the salvo count as misses while the rest are chosen. If the
+/// strategy runs out of cells it wants, the salvo is made up with cells not yet fired at.
+pub fn next_salvo(strategy: &mut dyn Strategy, game: &GameState, player: PlayerType) -> Vec<Location> {
+    let shots = game.shots_this_turn(player) as usize;
+    let remaining = remaining(game, player);
+    let mut board = known_board(game, player);
+    let mut salvo = Vec::new();
+    while salvo.len() < shots {
+        match strategy.next_shot(&board, &remaining) {
+            Some(location) => {
+                board[location.row as usize][location.col as usize] = EnemyBoardCell::Miss;
+                salvo.push(location);
+            }
+            None => break,
+        }
+    }
+    // the no-touch rule can rule out more cells than the salvo can spare
+    let unfired: Vec<Location> = cells(&game.as_enemy_board(&player), EnemyBoardCell::Empty).into_iter()
+        .filter(|location| !salvo.contains(location))
+        .take(shots - salvo.len())
+        .collect();
+    salvo.extend(unfired);
+    salvo
+}
+
+// the enemy board as the player sees it, with the cells the no-touch rule keeps clear around
+// sunk ships marked as misses so strategies don't waste shots on them
+fn known_board(game: &GameState, player: PlayerType) -> Vec<Vec<EnemyBoardCell>> {
+    let mut board = game.as_enemy_board(&player);
+    let neighbours = game.config().no_touch.neighbours();
+    for sunk in cells(&board, EnemyBoardCell::Sunk) {
+        for (row, col) in neighbours {
+            let (row, col) = (sunk.row as i64 + row, sunk.col as i64 + col);
+            if cell(&board, row, col) == Some(EnemyBoardCell::Empty) {
+                board[row as usize][col as usize] = EnemyBoardCell::Miss;
+            }
+        }
+    }
+    board
+}
+
+// the classes of the enemy ships still afloat
+fn remaining(game: &GameState, player: PlayerType) -> Vec<ShipType> {
+    game.ships(&PlayerType::other(&player)).iter()
+        .filter(|ship| !ship.sunk())
+        .map(|ship| ship.class.clone())
+        .collect()
+}
+
+fn cell(board: &[Vec<EnemyBoardCell>], row: i64, col: i64) -> Option<EnemyBoardCell> {
+    if row < 0 || col < 0 {
+        return None;
+    }
+    board.get(row as usize)?.get(col as usize).copied()
+}
+
+// every cell holding `wanted`, in row order
+fn cells(board: &[Vec<EnemyBoardCell>], wanted: EnemyBoardCell) -> Vec<Location> {
+    let mut found = Vec::new();
+    for (row, cells) in board.iter().enumerate() {
+        for (col, cell) in cells.iter().enumerate() {
+            if *cell == wanted {
+                found.push(Location { row: row as u32, col: col as u32 });
+            }
+        }
+    }
+    found
+}
+
+fn pick<R: Rng>(rng: &mut R, candidates: &[Location]) -> Option<Location> {
+    candidates.choose(rng).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use crate::ai::{next_salvo, next_shot, strategy, STRATEGIES};
+    use crate::config::{GameConfig, NoTouch, ShotsPerTurn};
+    use crate::fleet::FleetSpec;
+    use crate::game::{Direction, GameResult, GameState};
+    use crate::location::Location;
+    use crate::ship::ShipType;
+    use crate::player::{PlayerTurn, PlayerType};
+
+    #[test]
+    fn every_strategy_sinks_a_whole_fleet() {
+        for name in STRATEGIES {
+            let game = GameState::new();
+            let game = game.auto_place(PlayerType::Player1, &mut StdRng::seed_from_u64(1)).unwrap();
+            let mut game = game.auto_place(PlayerType::Player2, &mut StdRng::seed_from_u64(2)).unwrap();
+            let mut player1 = strategy(name, 3).unwrap();
+            let mut player2 = strategy(name, 4).unwrap();
+            // both players together can't need more shots than there are cells on their boards
+            let mut shots_left = 2 * game.width() * game.height();
+            while game.result() == GameResult::InProgress {
+                assert!(shots_left > 0, "{} is still firing after every cell was fired at", name);
+                shots_left -= 1;
+                let (player, strategy) = match game.turn() {
+                    PlayerTurn::Player1 => (PlayerType::Player1, &mut player1),
+                    _ => (PlayerType::Player2, &mut player2),
+                };
+                let location = next_shot(strategy.as_mut(), &game, player).unwrap();
+                game = game.fire(player, &location).unwrap_or_else(|e| panic!("{} fired a bad shot: {}", name, e));
+            }
+            let loser = match game.result() {
+                GameResult::Player1Win => PlayerType::Player2,
+                GameResult::Player2Win => PlayerType::Player1,
+                result => panic!("{} finished with {:?}", name, result),
+            };
+            assert!(game.ships(&loser).iter().all(|ship| ship.sunk()), "{}", name);
+        }
+    }
+
+    #[test]
+    fn salvos_have_no_repeated_cells() {
+        let config = GameConfig { shots_per_turn: ShotsPerTurn::ShipsAfloat, ..GameConfig::default() };
+        let game = GameState::with_config(config).unwrap();
+        let game = game.auto_place(PlayerType::Player1, &mut StdRng::seed_from_u64(1)).unwrap();
+        let game = game.auto_place(PlayerType::Player2, &mut StdRng::seed_from_u64(2)).unwrap();
+        let player = match game.turn() {
+            PlayerTurn::Player1 => PlayerType::Player1,
+            _ => PlayerType::Player2,
+        };
+        let shots = game.shots_this_turn(player) as usize;
+        for name in STRATEGIES {
+            let mut salvo = next_salvo(strategy(name, 3).unwrap().as_mut(), &game, player);
+            assert_eq!(salvo.len(), shots, "{}", name);
+            assert!(game.fire_salvo(player, &salvo).is_ok(), "{}", name);
+            salvo.sort_by_key(|location| (location.row, location.col));
+            salvo.dedup();
+            assert_eq!(salvo.len(), shots, "{}", name);
+        }
+    }
+
+    #[test]
+    fn every_strategy_plays_a_salvo_game_with_no_touch() {
+        let config = GameConfig { shots_per_turn: ShotsPerTurn::Fixed(5), no_touch: NoTouch::All, ..GameConfig::default() };
+        for name in STRATEGIES {
+            let game = GameState::with_config(config.clone()).unwrap();
+            let game = game.auto_place(PlayerType::Player1, &mut StdRng::seed_from_u64(1)).unwrap();
+            let mut game = game.auto_place(PlayerType::Player2, &mut StdRng::seed_from_u64(2)).unwrap();
+            let mut player1 = strategy(name, 3).unwrap();
+            let mut player2 = strategy(name, 4).unwrap();
+            let mut salvos_left = 2 * game.width() * game.height();
+            while game.result() == GameResult::InProgress {
+                assert!(salvos_left > 0, "{} is still firing after every cell was fired at", name);
+                salvos_left -= 1;
+                let (player, strategy) = match game.turn() {
+                    PlayerTurn::Player1 => (PlayerType::Player1, &mut player1),
+                    _ => (PlayerType::Player2, &mut player2),
+                };
+                let salvo = next_salvo(strategy.as_mut(), &game, player);
+                game = game.fire_salvo(player, &salvo).unwrap_or_else(|e| panic!("{} fired a bad salvo: {}", name, e));
+            }
+            assert!(matches!(game.result(), GameResult::Player1Win | GameResult::Player2Win), "{}", name);
+        }
+    }
+
+    #[test]
+    fn no_shots_next_to_a_sunk_ship_under_the_no_touch_rule() {
+        let fleet = FleetSpec::new(vec![(ShipType::Destroyer, 1), (ShipType::TorpedoBoat, 1)]);
+        let config = GameConfig { fleet, no_touch: NoTouch::All, ..GameConfig::with_grid(5, 5) };
+        let game = GameState::with_config(config).unwrap();
+        let game = game.place(PlayerType::Player1, ShipType::Destroyer, Location { row: 4, col: 0 }, Direction::Right).unwrap();
+        let game = game.place(PlayerType::Player1, ShipType::TorpedoBoat, Location { row: 0, col: 4 }, Direction::Right).unwrap();
+        let game = game.place(PlayerType::Player2, ShipType::Destroyer, Location { row: 0, col: 0 }, Direction::Right).unwrap();
+        let game = game.place(PlayerType::Player2, ShipType::TorpedoBoat, Location { row: 4, col: 4 }, Direction::Right).unwrap();
+        let game = game.fire(PlayerType::Player1, &Location { row: 0, col: 0 }).unwrap();
+        let game = game.fire(PlayerType::Player2, &Location { row: 2, col: 2 }).unwrap();
+        let game = game.fire(PlayerType::Player1, &Location { row: 0, col: 1 }).unwrap();
+        let game = game.fire(PlayerType::Player2, &Location { row: 2, col: 3 }).unwrap();
+
+        let around = [(0, 2), (1, 0), (1, 1), (1, 2)].map(|(row, col)| Location { row, col });
+        for name in STRATEGIES {
+            for seed in 0..20 {
+                let location = next_shot(strategy(name, seed).unwrap().as_mut(), &game, PlayerType::Player1).unwrap();
+                assert!(!around.contains(&location), "{} fired next to the sunk Destroyer at {}", name, location);
+            }
+        }
+    }
+
+    #[test]
+    fn unknown_strategy() {
+        assert!(strategy("Density", 0).is_some());
+        assert!(strategy("cheat", 0).is_none());
+    }
+}
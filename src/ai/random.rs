@@ -0,0 +1,39 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use crate::ai::{cells, pick, Strategy};
+use crate::game::EnemyBoardCell;
+use crate::location::Location;
+use crate::ship::ShipType;
+
+/// Fires at any cell it hasn't tried yet, paying no attention to hits.
+pub struct RandomShots {
+    rng: StdRng,
+}
+
+impl RandomShots {
+    pub fn new(seed: u64) -> Self {
+        RandomShots { rng: StdRng::seed_from_u64(seed) }
+    }
+}
+
+impl Strategy for RandomShots {
+    fn next_shot(&mut self, board: &[Vec<EnemyBoardCell>], _remaining: &[ShipType]) -> Option<Location> {
+        pick(&mut self.rng, &cells(board, EnemyBoardCell::Empty))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ai::{RandomShots, Strategy};
+    use crate::game::EnemyBoardCell;
+    use crate::location::Location;
+
+    #[test]
+    fn only_fires_at_empty_cells() {
+        let mut board = vec![vec![EnemyBoardCell::Miss; 3]; 3];
+        board[2][1] = EnemyBoardCell::Empty;
+        assert_eq!(RandomShots::new(0).next_shot(&board, &[]), Some(Location { row: 2, col: 1 }));
+        board[2][1] = EnemyBoardCell::Hit;
+        assert_eq!(RandomShots::new(0).next_shot(&board, &[]), None);
+    }
+}
@@ -0,0 +1,97 @@
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use battleship::referee::Referee;
+use battleship::{FleetSpec, GameConfig, Replay};
+
+const USAGE: &str = "Usage: battleship-referee [--addr <host:port>] [--grid <width>x<height>] [--fleet <fleet>] [--timeout <seconds>] [--replays <dir>]
+
+Bots connect over TCP and are paired in the order they connect, see docs/tcp.md.
+
+  --addr      Where to listen, defaults to 127.0.0.1:9000
+  --grid      Board size, defaults to 10x10
+  --fleet     classic, original, russian or a fleet such as Destroyer:2;Cruiser:1
+  --timeout   Seconds a bot gets to answer, defaults to 10
+  --replays   Directory to save a replay of every match in, view them with `battleship replay`";
+
+fn options() -> Result<(String, Referee, Option<PathBuf>), String> {
+    let mut addr = String::from("127.0.0.1:9000");
+    let mut replays = None;
+    let mut referee = Referee::default();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = args.next().ok_or_else(|| format!("{} needs a value", arg))?;
+        match arg.as_str() {
+            "--addr" => addr = value,
+            "--replays" => replays = Some(PathBuf::from(value)),
+            "--grid" => {
+                let size = value.split_once('x').map(|(w, h)| (w.parse::<u32>(), h.parse::<u32>()));
+                match size {
+                    Some((Ok(width), Ok(height))) => referee.config = GameConfig { width, height, ..referee.config },
+                    _ => return Err(format!("Expected --grid <width>x<height>, found {}", value)),
+                }
+            }
+            "--fleet" => {
+                referee.config.fleet = match FleetSpec::preset(&value) {
+                    Some(fleet) => fleet,
+                    None => FleetSpec::parse(&value, &referee.config.registry).map_err(|e| e.to_string())?,
+                }
+            }
+            "--timeout" => {
+                let seconds = value.parse::<u64>().map_err(|_| format!("Expected --timeout <seconds>, found {}", value))?;
+                referee.timeout = Duration::from_secs(seconds);
+            }
+            other => return Err(format!("Unknown option {}", other)),
+        }
+    }
+    referee.config.validate().map_err(|e| e.to_string())?;
+    Ok((addr, referee, replays))
+}
+
+fn main() {
+    let (addr, referee, replays) = match options() {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}\n\n{}", message, USAGE);
+            std::process::exit(1);
+        }
+    };
+    let listener = match TcpListener::bind(&addr) {
+        Ok(listener) => listener,
+        Err(error) => {
+            eprintln!("Could not listen on {}: {}", addr, error);
+            std::process::exit(2);
+        }
+    };
+    println!("Refereeing on {}", addr);
+    // matches run on their own threads, so they are numbered as they finish
+    let finished = Arc::new(AtomicUsize::new(0));
+    let served = referee.serve(listener, move |result| {
+        let matches = finished.fetch_add(1, Ordering::SeqCst) + 1;
+        let result = match result {
+            Ok(result) => result,
+            Err(error) => {
+                eprintln!("Match {} could not be played: {}", matches, error);
+                return;
+            }
+        };
+        match &result.forfeit {
+            Some(reason) => println!("Match {}: {} wins, {}", matches, result.winner, reason),
+            None => println!("Match {}: {} wins", matches, result.winner),
+        }
+        if let Some(dir) = &replays {
+            let path = dir.join(format!("match-{}.replay", matches));
+            let saved = Replay::new(&result.game).map_err(|e| e.to_string())
+                .and_then(|replay| std::fs::write(&path, replay.to_string()).map_err(|e| e.to_string()));
+            if let Err(error) = saved {
+                eprintln!("Could not save {}: {}", path.display(), error);
+            }
+        }
+    });
+    if let Err(error) = served {
+        eprintln!("{}", error);
+        std::process::exit(2);
+    }
+}
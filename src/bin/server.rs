@@ -0,0 +1,38 @@
+use battleship::server::Server;
+
+const USAGE: &str = "Usage: battleship-server [--addr <host:port>] [--ws-addr <host:port>]
+
+  --addr      Where to serve the HTTP API, defaults to 127.0.0.1:8080
+  --ws-addr   Where to accept WebSocket connections, defaults to 127.0.0.1:8081";
+
+fn main() {
+    let (mut addr, mut ws_addr) = (String::from("127.0.0.1:8080"), String::from("127.0.0.1:8081"));
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), args.next()) {
+            ("--addr", Some(value)) => addr = value,
+            ("--ws-addr", Some(value)) => ws_addr = value,
+            _ => {
+                eprintln!("{}", USAGE);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let server = match Server::bind(&addr) {
+        Ok(server) => server,
+        Err(error) => {
+            eprintln!("Could not listen on {}: {}", addr, error);
+            std::process::exit(2);
+        }
+    };
+    match server.listen_websockets(&ws_addr) {
+        Ok(ws_addr) => println!("Accepting WebSockets on ws://{}", ws_addr),
+        Err(error) => {
+            eprintln!("Could not listen on {}: {}", ws_addr, error);
+            std::process::exit(2);
+        }
+    }
+    println!("Listening on http://{}", server.addr());
+    server.run();
+}
@@ -0,0 +1,74 @@
+use battleship::ai::STRATEGIES;
+use battleship::simulation::Simulation;
+use battleship::{FleetSpec, GameConfig, KeepTurn, NoTouch, ShotsPerTurn};
+
+const USAGE: &str = "Usage: battleship-simulate <strategy> <strategy> [--games <n>] [--seed <n>] [--threads <n>] [--grid <width>x<height>] [--fleet <fleet>] [--salvo <ships|n>] [--again <hit|sink>] [--no-touch <rule>]
+
+Plays the two strategies against each other and reports how they did.
+
+  <strategy>  random, hunt, parity or density
+  --games     How many games to play, defaults to 100
+  --seed      Seed for fleet layouts and shots, defaults to 0
+  --threads   Threads to share the games between, defaults to the number of CPUs
+  --grid      Board size, defaults to 10x10
+  --fleet     classic, original, russian or a fleet such as Destroyer:2;Cruiser:1
+  --salvo     Fire a salvo each turn, one shot per ship afloat or a fixed count
+  --again     hit or sink, a hit or only a sink lets the strategy fire again
+  --no-touch  orthogonal, diagonal or all, the ways ships may not touch";
+
+fn number<T: std::str::FromStr>(option: &str, value: &str) -> Result<T, String> {
+    value.parse::<T>().map_err(|_| format!("Expected {} <n>, found {}", option, value))
+}
+
+fn options() -> Result<Simulation, String> {
+    let mut args = std::env::args().skip(1);
+    let (first, second) = match (args.next(), args.next()) {
+        (Some(first), Some(second)) if !first.starts_with("--") && !second.starts_with("--") => (first, second),
+        _ => return Err(format!("Expected two strategies out of {}", STRATEGIES.join(", "))),
+    };
+    let mut simulation = Simulation::new(&first, &second);
+    simulation.threads = std::thread::available_parallelism().map_or(1, |threads| threads.get());
+    while let Some(arg) = args.next() {
+        let value = args.next().ok_or_else(|| format!("{} needs a value", arg))?;
+        match arg.as_str() {
+            "--games" => simulation.games = number(&arg, &value)?,
+            "--seed" => simulation.seed = number(&arg, &value)?,
+            "--threads" => simulation.threads = number(&arg, &value)?,
+            "--grid" => {
+                let size = value.split_once('x').map(|(w, h)| (w.parse::<u32>(), h.parse::<u32>()));
+                match size {
+                    Some((Ok(width), Ok(height))) => simulation.config = GameConfig { width, height, ..simulation.config },
+                    _ => return Err(format!("Expected --grid <width>x<height>, found {}", value)),
+                }
+            }
+            "--fleet" => {
+                simulation.config.fleet = match FleetSpec::preset(&value) {
+                    Some(fleet) => fleet,
+                    None => FleetSpec::parse(&value, &simulation.config.registry).map_err(|e| e.to_string())?,
+                }
+            }
+            "--salvo" => simulation.config.shots_per_turn = value.parse::<ShotsPerTurn>().map_err(|e| e.to_string())?,
+            "--again" => simulation.config.keep_turn = value.parse::<KeepTurn>().map_err(|e| e.to_string())?,
+            "--no-touch" => simulation.config.no_touch = value.parse::<NoTouch>().map_err(|e| e.to_string())?,
+            other => return Err(format!("Unknown option {}", other)),
+        }
+    }
+    Ok(simulation)
+}
+
+fn main() {
+    let simulation = match options() {
+        Ok(simulation) => simulation,
+        Err(message) => {
+            eprintln!("{}\n\n{}", message, USAGE);
+            std::process::exit(1);
+        }
+    };
+    match simulation.run() {
+        Ok(report) => print!("{}", report),
+        Err(error) => {
+            eprintln!("{}\n\n{}", error, USAGE);
+            std::process::exit(1);
+        }
+    }
+}
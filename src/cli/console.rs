@@ -0,0 +1,39 @@
+use std::io::{self, BufRead, Write};
+
+/// Line based terminal IO, generic so games can be scripted in tests.
+pub struct Console<R: BufRead, W: Write> {
+    input: R,
+    output: W,
+}
+
+impl<R: BufRead, W: Write> Console<R, W> {
+    pub fn new(input: R, output: W) -> Self {
+        Console { input, output }
+    }
+
+    pub fn println(&mut self, text: &str) -> io::Result<()> {
+        writeln!(self.output, "{}", text)
+    }
+
+    /// Shows `text` and reads the reply, or `None` once the input is closed.
+    pub fn prompt(&mut self, text: &str) -> io::Result<Option<String>> {
+        write!(self.output, "{} ", text)?;
+        self.output.flush()?;
+        let mut line = String::new();
+        if self.input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        Ok(Some(line.trim().to_string()))
+    }
+
+    /// Clears the terminal so the next player can't see the last one's boards.
+    pub fn clear(&mut self) -> io::Result<()> {
+        write!(self.output, "\x1B[2J\x1B[H")?;
+        self.output.flush()
+    }
+
+    #[cfg(test)]
+    pub fn output(&self) -> &W {
+        &self.output
+    }
+}
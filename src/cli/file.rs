@@ -0,0 +1,81 @@
+use std::fs;
+use std::str::FromStr;
+use battleship::{GameState, Location, PlayerTurn, PlayerType, render_enemy_board, render_own_board};
+use crate::cli::input::{parse_placement, parse_player, parse_removal};
+use crate::cli::{game_config, CommandError};
+
+/// Runs one of the game file commands: loads the game at the path, applies the action
+/// and writes the game back, printing any messages the action produced.
+pub fn run(command: &str, args: &[String]) -> Result<(), CommandError> {
+    let (path, args) = match args.split_first() {
+        Some((path, args)) => (path, args.iter().map(|arg| arg.as_str()).collect::<Vec<&str>>()),
+        None => return Err(CommandError::Usage(format!("{} needs a game file", command))),
+    };
+    if command == "new" {
+        let config = game_config(&args)?;
+        let game = GameState::with_config(config)?;
+        return save(path, &game);
+    }
+
+    let game = load(path)?;
+    let (player, args) = match args.split_first() {
+        Some((player, args)) => (parse_player(player).map_err(CommandError::Usage)?, args),
+        None => return Err(CommandError::Usage(format!("{} needs a player", command))),
+    };
+    let registry = &game.config().registry;
+    let next = match command {
+        "place" => {
+            let (class, location, direction) = parse_placement(args, registry).map_err(CommandError::Usage)?;
+            game.place(player, class, location, direction)?
+        }
+        "remove" => {
+            let (class, location) = parse_removal(args, registry).map_err(CommandError::Usage)?;
+            game.remove(player, class, location)?
+        }
+        "fire" => {
+            if args.is_empty() {
+                return Err(CommandError::Usage(String::from("Expected fire <path> <player> <col,row>...")));
+            }
+            let salvo = args.iter()
+                .map(|location| Location::from_str(location))
+                .collect::<Result<Vec<Location>, _>>()
+                .map_err(|e| CommandError::Usage(e.to_string()))?;
+            game.fire_salvo(player, &salvo)?
+        }
+        "show" => {
+            println!("{}", show(&game, player));
+            return Ok(());
+        }
+        _ => return Err(CommandError::Usage(format!("Unknown command {}", command))),
+    };
+    for message in next.messages().iter().skip(game.messages().len()) {
+        println!("{}", message);
+    }
+    save(path, &next)
+}
+
+fn load(path: &str) -> Result<GameState, CommandError> {
+    let serialized = fs::read_to_string(path)?;
+    Ok(GameState::from_str(&serialized)?)
+}
+
+fn save(path: &str, game: &GameState) -> Result<(), CommandError> {
+    Ok(fs::write(path, game.to_string())?)
+}
+
+// what one player is allowed to see: their own fleet, their shots and whose turn it is
+fn show(game: &GameState, player: PlayerType) -> String {
+    let turn = match game.turn() {
+        PlayerTurn::Player1 => "Player 1 to fire",
+        PlayerTurn::Player2 => "Player 2 to fire",
+        PlayerTurn::Either => "Placing ships",
+        PlayerTurn::Neither => "Game over",
+    };
+    format!(
+        "{} - {}\n\nYour fleet\n{}\n\nEnemy waters\n{}",
+        game.result(),
+        turn,
+        render_own_board(&game.as_own_board(&player)),
+        render_enemy_board(&game.as_enemy_board(&player)),
+    )
+}
@@ -0,0 +1,239 @@
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+use battleship::{GameResult, GameState, Location, PlayerTurn, PlayerType, render_enemy_board, render_own_board};
+use crate::cli::console::Console;
+use crate::cli::input::{parse_placement, parse_removal};
+
+/// Two players sharing one terminal: each places their fleet, then they take turns firing.
+/// The screen is cleared between turns so neither player sees the other's fleet.
+/// Returns the final state, or `None` if a player quit.
+pub fn play<R: BufRead, W: Write>(console: &mut Console<R, W>, game: GameState) -> io::Result<Option<GameState>> {
+    let mut game = game;
+    for player in [PlayerType::Player1, PlayerType::Player2] {
+        game = match setup(console, game, player)? {
+            Some(game) => game,
+            None => return Ok(None),
+        };
+    }
+
+    let mut seen = game.messages().len();
+    let mut shooter = None;
+    loop {
+        let player = match game.turn() {
+            PlayerTurn::Player1 => PlayerType::Player1,
+            PlayerTurn::Player2 => PlayerType::Player2,
+            _ => break,
+        };
+        // a player who keeps the turn fires again without handing the keyboard over
+        if shooter != Some(player) {
+            handover(console, player)?;
+        }
+        console.println(&boards(&game, player))?;
+        game = match take_shot(console, game, player, &mut seen)? {
+            Some(game) => game,
+            None => return Ok(None),
+        };
+        shooter = Some(player);
+        if game.result() == GameResult::InProgress && game.turn() != &PlayerTurn::from(&player) {
+            console.prompt("Press Enter to end your turn.")?;
+        }
+    }
+
+    console.prompt("Press Enter to reveal both fleets.")?;
+    console.clear()?;
+    for player in [PlayerType::Player1, PlayerType::Player2] {
+        console.println(&format!("{}'s fleet\n{}\n", player, render_own_board(&game.as_own_board(&player))))?;
+    }
+    Ok(Some(game))
+}
+
+// asks the player to hand over the keyboard before anything of theirs is shown
+fn handover<R: BufRead, W: Write>(console: &mut Console<R, W>, player: PlayerType) -> io::Result<()> {
+    console.clear()?;
+    console.prompt(&format!("Pass the keyboard to {} and press Enter.", player))?;
+    console.clear()
+}
+
+pub(super) fn boards(game: &GameState, player: PlayerType) -> String {
+    let own = render_own_board(&game.as_own_board(&player));
+    let enemy = render_enemy_board(&game.as_enemy_board(&player));
+    side_by_side("Your fleet", &own, "Enemy waters", &enemy)
+}
+
+/// Lays two rendered boards out next to each other under their titles.
+pub(super) fn side_by_side(left_title: &str, left: &str, right_title: &str, right: &str) -> String {
+    let width = left.lines().map(|line| line.len()).max().unwrap_or(0);
+    let mut lines = vec![format!("{:<width$}    {}", left_title, right_title, width = width)];
+    for (left, right) in left.lines().zip(right.lines()) {
+        lines.push(format!("{:<width$}    {}", left, right, width = width));
+    }
+    lines.join("\n")
+}
+
+fn setup<R: BufRead, W: Write>(console: &mut Console<R, W>, game: GameState, player: PlayerType) -> io::Result<Option<GameState>> {
+    handover(console, player)?;
+    place_fleet(console, game, player)
+}
+
+/// Prompts the player to place their ships until their whole fleet is on the board.
+/// Returns `None` if they quit.
+pub(super) fn place_fleet<R: BufRead, W: Write>(console: &mut Console<R, W>, game: GameState, player: PlayerType) -> io::Result<Option<GameState>> {
+    let mut game = game;
+    while game.result() == GameResult::InSetup && !game.ready(player) {
+        console.println(&render_own_board(&game.as_own_board(&player)))?;
+        let unplaced: Vec<String> = game.ships(&player).iter()
+            .filter(|ship| ship.locations.is_empty())
+            .map(|ship| format!("{} ({})", ship.class, ship.class.size()))
+            .collect();
+        console.println(&format!("Ships left to place: {}", unplaced.join(", ")))?;
+        let line = match console.prompt(&format!("{}> place <class> <col,row> <up|down|left|right> | remove <class> <col,row> | auto | quit:", player))? {
+            Some(line) => line,
+            None => return Ok(None),
+        };
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let registry = &game.config().registry;
+        let next = match words.split_first() {
+            Some((&"place", args)) => parse_placement(args, registry)
+                .and_then(|(class, location, direction)| game.place(player, class, location, direction).map_err(|e| e.to_string())),
+            Some((&"remove", args)) => parse_removal(args, registry)
+                .and_then(|(class, location)| game.remove(player, class, location).map_err(|e| e.to_string())),
+            Some((&"auto", [])) => game.auto_place(player, &mut rand::thread_rng()).map_err(|e| e.to_string()),
+            Some((&"quit", _)) => return Ok(None),
+            _ => Err(String::from("Unknown command")),
+        };
+        match next {
+            Ok(next) => game = next,
+            Err(message) => console.println(&message)?,
+        }
+    }
+    Ok(Some(game))
+}
+
+/// Prompts until the player fires a valid shot, or a whole salvo on one line under the Salvo
+/// rules, printing what happened since they last looked.
+pub(super) fn take_shot<R: BufRead, W: Write>(console: &mut Console<R, W>, game: GameState, player: PlayerType, seen: &mut usize) -> io::Result<Option<GameState>> {
+    for message in game.messages().iter().skip(*seen) {
+        console.println(message)?;
+    }
+    *seen = game.messages().len();
+    loop {
+        let prompt = match game.shots_this_turn(player) {
+            1 => format!("{}> fire <col,row> | quit:", player),
+            shots => format!("{}> fire {} x <col,row> | quit:", player, shots),
+        };
+        let line = match console.prompt(&prompt)? {
+            Some(line) => line,
+            None => return Ok(None),
+        };
+        let targets = match line.split_whitespace().collect::<Vec<&str>>().as_slice() {
+            ["quit"] => return Ok(None),
+            ["fire", targets @ ..] | targets if !targets.is_empty() => targets.to_vec(),
+            _ => {
+                console.println("Unknown command")?;
+                continue;
+            }
+        };
+        let next = targets.iter()
+            .map(|target| Location::from_str(target))
+            .collect::<Result<Vec<Location>, _>>()
+            .and_then(|salvo| game.fire_salvo(player, &salvo));
+        match next {
+            Ok(next) => {
+                for message in next.messages().iter().skip(*seen) {
+                    console.println(message)?;
+                }
+                *seen = next.messages().len();
+                return Ok(Some(next));
+            }
+            Err(error) => console.println(&error.to_string())?,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use battleship::{FleetSpec, GameConfig, GameResult, GameState, KeepTurn, ShipType, ShotsPerTurn};
+    use crate::cli::console::Console;
+    use crate::cli::hotseat::play;
+
+    fn small_game() -> GameState {
+        let config = GameConfig { fleet: FleetSpec::new(vec![(ShipType::Destroyer, 1)]), ..GameConfig::with_grid(3, 3) };
+        GameState::with_config(config).unwrap()
+    }
+
+    #[test]
+    fn play_a_game_to_the_end() {
+        let script = [
+            "", "place destroyer 0,0 right",
+            "", "place destroyer 5,5 down", "place destroyer 2,1 down",
+            "", "fire 2,1", "",
+            "", "0,2", "",
+            "", "fire 2,1", "fire 2,2",
+        ].join("\n");
+        let mut console = Console::new(Cursor::new(script), Vec::new());
+        let game = play(&mut console, small_game()).unwrap().unwrap();
+        assert_eq!(game.result(), GameResult::Player1Win);
+
+        let output = String::from_utf8(console.output().clone()).unwrap();
+        assert!(output.contains("Pass the keyboard to Player 2 and press Enter."));
+        assert!(output.contains("Not enough room to place a Destroyer at (5, 5) Down"));
+        assert!(output.contains("Player 1 fires at (2, 1) and hits Player 2's ship!"));
+        assert!(output.contains("Player 2 fires at (0, 2) and misses!"));
+        assert!(output.contains("Cannot fire on (2, 1), you have already fired there!"));
+        assert!(output.contains("Game over. Player 1 wins!"));
+    }
+
+    #[test]
+    fn salvos_are_fired_on_one_line() {
+        let config = GameConfig { shots_per_turn: ShotsPerTurn::Fixed(2), ..small_game().config().clone() };
+        let script = [
+            "", "place destroyer 0,0 right",
+            "", "place destroyer 2,1 down",
+            "", "2,1", "fire 2,1 2,2",
+        ].join("\n");
+        let mut console = Console::new(Cursor::new(script), Vec::new());
+        let game = play(&mut console, GameState::with_config(config).unwrap()).unwrap().unwrap();
+        assert_eq!(game.result(), GameResult::Player1Win);
+
+        let output = String::from_utf8(console.output().clone()).unwrap();
+        assert!(output.contains("Player 1> fire 2 x <col,row> | quit:"));
+        assert!(output.contains("Expected a salvo of 2 shots, found 1."));
+        assert!(output.contains("Player 1 sunk Player 2's Destroyer!"));
+    }
+
+    #[test]
+    fn a_hit_lets_the_player_fire_again() {
+        let config = GameConfig { keep_turn: KeepTurn::OnHit, ..small_game().config().clone() };
+        let script = [
+            "", "place destroyer 0,0 right",
+            "", "place destroyer 2,1 down",
+            "", "2,1", "2,2",
+        ].join("\n");
+        let mut console = Console::new(Cursor::new(script), Vec::new());
+        let game = play(&mut console, GameState::with_config(config).unwrap()).unwrap().unwrap();
+        assert_eq!(game.result(), GameResult::Player1Win);
+
+        let output = String::from_utf8(console.output().clone()).unwrap();
+        assert!(output.contains("Player 1 fires again!"));
+        assert!(!output.contains("Press Enter to end your turn."));
+    }
+
+    #[test]
+    fn auto_places_the_rest_of_the_fleet() {
+        let mut console = Console::new(Cursor::new("\nauto\n\nauto\n"), Vec::new());
+        // both fleets are placed, then the input runs out on the first turn
+        assert!(play(&mut console, small_game()).unwrap().is_none());
+        let output = String::from_utf8(console.output().clone()).unwrap();
+        assert!(output.contains("Player 1> fire <col,row> | quit:"));
+        assert!(!output.contains("Unknown command"));
+    }
+
+    #[test]
+    fn quitting_ends_the_game() {
+        let mut console = Console::new(Cursor::new("\nquit\n"), Vec::new());
+        assert!(play(&mut console, small_game()).unwrap().is_none());
+        let mut console = Console::new(Cursor::new(""), Vec::new());
+        assert!(play(&mut console, small_game()).unwrap().is_none());
+    }
+}
@@ -0,0 +1,64 @@
+use std::str::FromStr;
+use battleship::{Direction, Location, PlayerType, ShipRegistry, ShipType};
+
+/// Reads a player given as `1` or `2`.
+pub fn parse_player(input: &str) -> Result<PlayerType, String> {
+    match input {
+        "1" => Ok(PlayerType::Player1),
+        "2" => Ok(PlayerType::Player2),
+        _ => Err(format!("Expected player 1 or 2, found {}", input)),
+    }
+}
+
+/// Reads `<class> <col,row> <direction>`. Class names may contain spaces, so the
+/// location and direction are taken from the end.
+pub fn parse_placement(args: &[&str], registry: &ShipRegistry) -> Result<(ShipType, Location, Direction), String> {
+    if args.len() < 3 {
+        return Err(String::from("Expected <class> <col,row> <up|down|left|right>"));
+    }
+    let (class, rest) = args.split_at(args.len() - 2);
+    let class = registry.parse(&class.join(" ")).map_err(|e| e.to_string())?;
+    let location = Location::from_str(rest[0]).map_err(|e| e.to_string())?;
+    let direction = Direction::from_str(rest[1]).map_err(|e| e.to_string())?;
+    Ok((class, location, direction))
+}
+
+/// Reads `<class> <col,row>`.
+pub fn parse_removal(args: &[&str], registry: &ShipRegistry) -> Result<(ShipType, Location), String> {
+    if args.len() < 2 {
+        return Err(String::from("Expected <class> <col,row>"));
+    }
+    let (class, rest) = args.split_at(args.len() - 1);
+    let class = registry.parse(&class.join(" ")).map_err(|e| e.to_string())?;
+    let location = Location::from_str(rest[0]).map_err(|e| e.to_string())?;
+    Ok((class, location))
+}
+
+#[cfg(test)]
+mod tests {
+    use battleship::{Direction, Location, PlayerType, ShipRegistry, ShipType};
+    use crate::cli::input::{parse_placement, parse_player, parse_removal};
+
+    #[test]
+    fn player() {
+        assert_eq!(parse_player("2"), Ok(PlayerType::Player2));
+        assert!(parse_player("3").is_err());
+    }
+
+    #[test]
+    fn placement() {
+        let registry = ShipRegistry::standard();
+        let placement = parse_placement(&["torpedo", "boat", "3,4", "down"], &registry).unwrap();
+        assert_eq!(placement, (ShipType::TorpedoBoat, Location { row: 4, col: 3 }, Direction::Down));
+        assert!(parse_placement(&["Carrier", "3,4"], &registry).is_err());
+        assert!(parse_placement(&["Carrier", "3,4", "sideways"], &registry).is_err());
+        assert!(parse_placement(&["Rowboat", "3,4", "up"], &registry).is_err());
+    }
+
+    #[test]
+    fn removal() {
+        let registry = ShipRegistry::standard();
+        assert_eq!(parse_removal(&["Carrier", "0,1"], &registry).unwrap(), (ShipType::Carrier, Location { row: 1, col: 0 }));
+        assert!(parse_removal(&["0,1"], &registry).is_err());
+    }
+}
@@ -0,0 +1,289 @@
+use std::fmt::Display;
+use std::io;
+use battleship::ai::{self, Strategy};
+use battleship::{FirstShooter, FleetSpec, GameConfig, GameError, GameState, KeepTurn, NoTouch, ShotsPerTurn};
+use crate::cli::console::Console;
+
+mod console;
+mod file;
+mod hotseat;
+mod input;
+mod replay;
+mod solo;
+
+const USAGE: &str = "Usage: battleship <command> [options]
+
+Commands:
+  play [options]                                  Two players take turns at this terminal
+  play --vs-ai <level> [options]                  Play against the computer
+  play --replay <path> [options]                  Save a replay of the game when it ends
+  replay <path>                                   Step through a replay or a saved game
+  new <path> [options]                            Start a game and save it to a file
+  place <path> <player> <class> <col,row> <dir>   Place a ship, dir is up, down, left or right
+  remove <path> <player> <class> <col,row>        Take back a placed ship
+  fire <path> <player> <col,row>...               Fire a shot, or a whole salvo
+  show <path> <player>                            Print what the player can see
+
+<player> is 1 or 2. The file commands load the game, apply one action and save it again.
+
+<level> is easy, medium or hard, or one of the strategies random, hunt, parity or density.
+
+Options:
+  --grid <width>x<height>                 Board size, defaults to 10x10
+  --fleet <classic|original|russian>      Ships each player places, defaults to original
+  --first <player1|random>                Who fires first, defaults to player1
+  --salvo <ships|count>                   Fire a salvo each turn, one shot per ship afloat
+                                          or a fixed count
+  --again <hit|sink>                      A hit, or only a sink, lets the player fire again
+  --no-touch <orthogonal|diagonal|all>    Ships may not touch side by side, at a corner
+                                          or at all
+
+Exit codes:
+  0   Success
+  1   Bad command or options
+  2   The game file could not be read or written
+  10  Not your turn                 11  Wrong phase of the game
+  12  Out of bounds                 13  No room for the ship
+  14  Ships overlap                 15  Already fired there
+  16  No ships of the class left    17  Ship not found
+  18  Invalid ship class            19  Invalid game configuration
+  20  The game file could not be parsed
+  21  No room left for the rest of the fleet
+  22  The game's history does not add up
+  23  Wrong number of shots in a salvo
+  24  The ship would touch another ship";
+
+/// Why a command failed, each kind has its own exit code so scripts can tell them apart.
+#[derive(Debug)]
+pub enum CommandError {
+    Usage(String),
+    Io(io::Error),
+    Game(GameError),
+}
+
+impl CommandError {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CommandError::Usage(_) => 1,
+            CommandError::Io(_) => 2,
+            CommandError::Game(error) => match error {
+                GameError::NotYourTurn { .. } => 10,
+                GameError::WrongPhase { .. } => 11,
+                GameError::OutOfBounds { .. } => 12,
+                GameError::NoRoom { .. } => 13,
+                GameError::Overlap { .. } => 14,
+                GameError::AlreadyFired { .. } => 15,
+                GameError::NoShipsOfClassLeft { .. } => 16,
+                GameError::ShipNotFound { .. } => 17,
+                GameError::InvalidShipClass { .. } => 18,
+                GameError::InvalidConfig { .. } => 19,
+                GameError::ParseError { .. } => 20,
+                GameError::NoLayout { .. } => 21,
+                GameError::InvalidEvent { .. } => 22,
+                GameError::WrongSalvoSize { .. } => 23,
+                GameError::Touching { .. } => 24,
+            },
+        }
+    }
+}
+
+impl Display for CommandError {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> std::result::Result<(), ::std::fmt::Error> {
+        match self {
+            CommandError::Usage(message) => write!(f, "{}\n\n{}", message, USAGE),
+            CommandError::Io(error) => write!(f, "{}", error),
+            CommandError::Game(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl From<io::Error> for CommandError {
+    fn from(error: io::Error) -> Self {
+        CommandError::Io(error)
+    }
+}
+
+impl From<GameError> for CommandError {
+    fn from(error: GameError) -> Self {
+        CommandError::Game(error)
+    }
+}
+
+/// Runs the command line client and returns the process exit code.
+pub fn run(args: &[String]) -> i32 {
+    let result = match args.first().map(|arg| arg.as_str()) {
+        Some("play") => play(&args[1..]),
+        Some(command @ ("new" | "place" | "remove" | "fire" | "show")) => file::run(command, &args[1..]),
+        Some("replay") => view_replay(&args[1..]),
+        Some("help") | Some("--help") | None => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        Some(command) => Err(CommandError::Usage(format!("Unknown command {}", command))),
+    };
+    match result {
+        Ok(()) => 0,
+        Err(error) => {
+            eprintln!("{}", error);
+            error.exit_code()
+        }
+    }
+}
+
+fn play(args: &[String]) -> Result<(), CommandError> {
+    let mut args: Vec<&str> = args.iter().map(|arg| arg.as_str()).collect();
+    let opponent = match take_option(&mut args, "--vs-ai")? {
+        Some(level) => Some(computer(level)?),
+        None => None,
+    };
+    let replay = take_option(&mut args, "--replay")?;
+    let game = GameState::with_config(game_config(&args)?)?;
+    let stdin = io::stdin();
+    let mut console = Console::new(stdin.lock(), io::stdout());
+    let game = match opponent {
+        Some(mut opponent) => solo::play(&mut console, game, opponent.as_mut())?,
+        None => hotseat::play(&mut console, game)?,
+    };
+    if let (Some(path), Some(game)) = (replay, game) {
+        replay::save(path, &game)?;
+        println!("Replay saved to {}", path);
+    }
+    Ok(())
+}
+
+// removes `--name <value>` from the arguments, returning the value
+fn take_option<'a>(args: &mut Vec<&'a str>, name: &str) -> Result<Option<&'a str>, CommandError> {
+    let index = match args.iter().position(|arg| *arg == name) {
+        Some(index) => index,
+        None => return Ok(None),
+    };
+    let value = *args.get(index + 1).ok_or_else(|| CommandError::Usage(format!("{} needs a value", name)))?;
+    args.drain(index..index + 2);
+    Ok(Some(value))
+}
+
+fn view_replay(args: &[String]) -> Result<(), CommandError> {
+    let path = match args {
+        [path] => path,
+        _ => return Err(CommandError::Usage(String::from("Expected replay <path>"))),
+    };
+    let replay = replay::load(path)?;
+    let stdin = io::stdin();
+    let mut console = Console::new(stdin.lock(), io::stdout());
+    Ok(replay::view(&mut console, &replay)?)
+}
+
+// the computer's strategy for a difficulty level or strategy name
+fn computer(level: &str) -> Result<Box<dyn Strategy + Send>, CommandError> {
+    let name = match level {
+        "easy" => "random",
+        "medium" => "hunt",
+        "hard" => "density",
+        name => name,
+    };
+    ai::strategy(name, rand::random()).ok_or_else(|| CommandError::Usage(format!("Unknown level {}", level)))
+}
+
+// reads the --grid, --fleet and --first options
+fn game_config(args: &[&str]) -> Result<GameConfig, CommandError> {
+    let mut config = GameConfig::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| CommandError::Usage(format!("{} needs a value", arg)));
+        match *arg {
+            "--grid" => {
+                let grid = value()?;
+                let size = grid.split_once('x').map(|(w, h)| (w.parse::<u32>(), h.parse::<u32>()));
+                match size {
+                    Some((Ok(width), Ok(height))) => { config.width = width; config.height = height; }
+                    _ => return Err(CommandError::Usage(format!("Expected --grid <width>x<height>, found {}", grid))),
+                }
+            }
+            "--fleet" => {
+                let fleet = value()?;
+                config.fleet = FleetSpec::preset(fleet).ok_or_else(|| CommandError::Usage(format!("Unknown fleet {}", fleet)))?;
+            }
+            "--first" => {
+                config.first_shooter = match *value()? {
+                    "player1" => FirstShooter::Player1,
+                    "random" => FirstShooter::Random,
+                    other => return Err(CommandError::Usage(format!("Unknown first shooter {}", other))),
+                }
+            }
+            "--salvo" => {
+                let salvo = value()?;
+                config.shots_per_turn = match salvo.parse::<ShotsPerTurn>() {
+                    Ok(shots) if shots != ShotsPerTurn::Single => shots,
+                    _ => return Err(CommandError::Usage(format!("Expected --salvo <ships|count>, found {}", salvo))),
+                };
+            }
+            "--again" => {
+                let again = value()?;
+                config.keep_turn = match again.parse::<KeepTurn>() {
+                    Ok(keep_turn) if keep_turn != KeepTurn::Never => keep_turn,
+                    _ => return Err(CommandError::Usage(format!("Expected --again <hit|sink>, found {}", again))),
+                };
+            }
+            "--no-touch" => {
+                let no_touch = value()?;
+                config.no_touch = match no_touch.parse::<NoTouch>() {
+                    Ok(no_touch) if no_touch != NoTouch::Off => no_touch,
+                    _ => return Err(CommandError::Usage(format!("Expected --no-touch <orthogonal|diagonal|all>, found {}", no_touch))),
+                };
+            }
+            other => return Err(CommandError::Usage(format!("Unknown option {}", other))),
+        }
+    }
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use battleship::{FirstShooter, FleetSpec, GameError, KeepTurn, Location, NoTouch, ShotsPerTurn};
+    use crate::cli::{computer, game_config, CommandError};
+
+    #[test]
+    fn options_build_a_config() {
+        let config = game_config(&["--grid", "8x9", "--fleet", "classic", "--first", "random"]).unwrap();
+        assert_eq!((config.width, config.height), (8, 9));
+        assert_eq!(config.fleet, FleetSpec::classic());
+        assert_eq!(config.first_shooter, FirstShooter::Random);
+        assert_eq!(config.shots_per_turn, ShotsPerTurn::Single);
+
+        let config = game_config(&["--salvo", "ships"]).unwrap();
+        assert_eq!(config.shots_per_turn, ShotsPerTurn::ShipsAfloat);
+        let config = game_config(&["--salvo", "3"]).unwrap();
+        assert_eq!(config.shots_per_turn, ShotsPerTurn::Fixed(3));
+        let config = game_config(&["--again", "sink"]).unwrap();
+        assert_eq!(config.keep_turn, KeepTurn::OnSink);
+        let config = game_config(&["--no-touch", "all"]).unwrap();
+        assert_eq!(config.no_touch, NoTouch::All);
+    }
+
+    #[test]
+    fn bad_options_are_rejected() {
+        assert!(game_config(&["--grid", "8"]).is_err());
+        assert!(game_config(&["--fleet"]).is_err());
+        assert!(game_config(&["--colour", "blue"]).is_err());
+        assert!(game_config(&["--salvo", "1"]).is_err());
+        assert!(game_config(&["--again", "miss"]).is_err());
+        assert!(game_config(&["--no-touch", "off"]).is_err());
+    }
+
+    #[test]
+    fn computer_levels() {
+        for level in ["easy", "medium", "hard", "random", "hunt", "parity", "density"] {
+            assert!(computer(level).is_ok(), "{}", level);
+        }
+        assert!(matches!(computer("impossible"), Err(CommandError::Usage(_))));
+    }
+
+    #[test]
+    fn exit_codes() {
+        assert_eq!(CommandError::Usage(String::from("Unknown command")).exit_code(), 1);
+        assert_eq!(CommandError::from(io::Error::from(io::ErrorKind::NotFound)).exit_code(), 2);
+        assert_eq!(CommandError::from(GameError::AlreadyFired { location: Location { row: 0, col: 0 } }).exit_code(), 15);
+        assert_eq!(CommandError::from(GameError::parse("", String::from("empty"))).exit_code(), 20);
+    }
+}
@@ -0,0 +1,81 @@
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+use battleship::{GameState, PlayerType, Replay, render_own_board};
+use crate::cli::console::Console;
+use crate::cli::hotseat::side_by_side;
+use crate::cli::CommandError;
+
+/// Reads a replay file, or a saved game which is replayed from its history.
+pub fn load(path: &str) -> Result<Replay, CommandError> {
+    let serialized = fs::read_to_string(path)?;
+    if serialized.starts_with("battleship-replay|") {
+        return Ok(Replay::from_str(&serialized)?);
+    }
+    Ok(Replay::new(&GameState::from_str(&serialized)?)?)
+}
+
+pub fn save(path: &str, game: &GameState) -> Result<(), CommandError> {
+    Ok(fs::write(path, Replay::new(game)?.to_string())?)
+}
+
+/// Shows the replay one move at a time with both fleets uncovered, until the viewer quits.
+pub fn view<R: BufRead, W: Write>(console: &mut Console<R, W>, replay: &Replay) -> io::Result<()> {
+    let mut step = 0;
+    loop {
+        console.clear()?;
+        let game = replay.at(step);
+        let previous = if step == 0 { 0 } else { replay.at(step - 1).messages().len() };
+        console.println(&format!("Move {} of {}", step, replay.moves()))?;
+        for message in game.messages().iter().skip(previous) {
+            console.println(message)?;
+        }
+        console.println(&side_by_side(
+            "Player 1's fleet", &render_own_board(&game.as_own_board(&PlayerType::Player1)),
+            "Player 2's fleet", &render_own_board(&game.as_own_board(&PlayerType::Player2)),
+        ))?;
+        let line = match console.prompt("next | previous | jump <move> | quit:")? {
+            Some(line) => line,
+            None => return Ok(()),
+        };
+        step = match line.split_whitespace().collect::<Vec<&str>>().as_slice() {
+            [] | ["n"] | ["next"] => (step + 1).min(replay.moves()),
+            ["p"] | ["previous"] => step.saturating_sub(1),
+            ["j", target] | ["jump", target] | [target] if target.parse::<usize>().is_ok() =>
+                target.parse::<usize>().unwrap_or(step).min(replay.moves()),
+            ["q"] | ["quit"] => return Ok(()),
+            _ => step,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use battleship::{Direction, FleetSpec, GameConfig, GameState, Location, PlayerType, Replay, ShipType};
+    use crate::cli::console::Console;
+    use crate::cli::replay::view;
+
+    fn replay() -> Replay {
+        let config = GameConfig { fleet: FleetSpec::new(vec![(ShipType::TorpedoBoat, 1)]), ..GameConfig::with_grid(2, 2) };
+        let game = GameState::with_config(config).unwrap();
+        let game = game.place(PlayerType::Player1, ShipType::TorpedoBoat, Location { row: 0, col: 0 }, Direction::Up).unwrap();
+        let game = game.place(PlayerType::Player2, ShipType::TorpedoBoat, Location { row: 1, col: 1 }, Direction::Up).unwrap();
+        let game = game.fire(PlayerType::Player1, &Location { row: 0, col: 1 }).unwrap();
+        let game = game.fire(PlayerType::Player2, &Location { row: 1, col: 0 }).unwrap();
+        let game = game.fire(PlayerType::Player1, &Location { row: 1, col: 1 }).unwrap();
+        Replay::new(&game).unwrap()
+    }
+
+    #[test]
+    fn step_forwards_backwards_and_jump() {
+        let mut console = Console::new(Cursor::new("next\n\nprevious\njump 3\nnext\nq\n"), Vec::new());
+        view(&mut console, &replay()).unwrap();
+        let output = String::from_utf8(console.output().clone()).unwrap();
+        let moves: Vec<&str> = output.split("\x1B[2J\x1B[H").filter_map(|screen| screen.lines().next()).filter(|line| !line.is_empty()).collect();
+        assert_eq!(moves, ["Move 0 of 3", "Move 1 of 3", "Move 2 of 3", "Move 1 of 3", "Move 3 of 3", "Move 3 of 3"]);
+        assert!(output.contains("Player 1 fires at (1, 0) and misses!"));
+        assert!(output.contains("Game over. Player 1 wins!"));
+        assert!(output.contains("Player 1's fleet"));
+    }
+}
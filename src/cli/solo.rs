@@ -0,0 +1,88 @@
+use std::io::{self, BufRead, Write};
+use battleship::ai::{self, Strategy};
+use battleship::{GameResult, GameState, PlayerTurn, PlayerType, render_own_board};
+use crate::cli::console::Console;
+use crate::cli::hotseat::{boards, place_fleet, take_shot};
+
+// the computer always plays the second seat
+const COMPUTER: PlayerType = PlayerType::Player2;
+
+/// One player against the computer. The computer places its fleet at random, then answers
+/// each of the player's shots straight away using `strategy`.
+/// Returns the final state, or `None` if the player quit.
+pub fn play<R: BufRead, W: Write>(console: &mut Console<R, W>, game: GameState, strategy: &mut dyn Strategy) -> io::Result<Option<GameState>> {
+    let game = game.auto_place(COMPUTER, &mut rand::thread_rng()).map_err(io::Error::other)?;
+    let mut game = match place_fleet(console, game, PlayerType::Player1)? {
+        Some(game) => game,
+        None => return Ok(None),
+    };
+
+    let mut seen = game.messages().len();
+    loop {
+        game = match game.turn() {
+            PlayerTurn::Player1 => {
+                // the computer's answer comes before the boards, so the board shows where it fired
+                for message in game.messages().iter().skip(seen) {
+                    console.println(message)?;
+                }
+                seen = game.messages().len();
+                console.println(&boards(&game, PlayerType::Player1))?;
+                match take_shot(console, game, PlayerType::Player1, &mut seen)? {
+                    Some(game) => game,
+                    None => return Ok(None),
+                }
+            }
+            PlayerTurn::Player2 => {
+                let salvo = ai::next_salvo(strategy, &game, COMPUTER);
+                if salvo.is_empty() {
+                    return Err(io::Error::other("The computer has nowhere left to fire"));
+                }
+                game.fire_salvo(COMPUTER, &salvo).map_err(io::Error::other)?
+            }
+            _ => break,
+        };
+    }
+
+    for message in game.messages().iter().skip(seen) {
+        console.println(message)?;
+    }
+    if game.result() == GameResult::Player2Win {
+        console.println(&format!("The computer's fleet\n{}", render_own_board(&game.as_own_board(&COMPUTER))))?;
+    }
+    Ok(Some(game))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use battleship::ai::RandomShots;
+    use battleship::{FleetSpec, GameConfig, GameResult, GameState, ShipType};
+    use crate::cli::console::Console;
+    use crate::cli::solo::play;
+
+    #[test]
+    fn play_against_the_computer() {
+        let config = GameConfig { fleet: FleetSpec::new(vec![(ShipType::Destroyer, 1)]), ..GameConfig::with_grid(3, 3) };
+        let game = GameState::with_config(config).unwrap();
+        let mut script = vec![String::from("place destroyer 0,0 right")];
+        for row in 0..3 {
+            for col in 0..3 {
+                script.push(format!("{},{}", col, row));
+            }
+        }
+        let mut console = Console::new(Cursor::new(script.join("\n")), Vec::new());
+        let game = play(&mut console, game, &mut RandomShots::new(5)).unwrap().unwrap();
+        assert!(matches!(game.result(), GameResult::Player1Win | GameResult::Player2Win));
+
+        let output = String::from_utf8(console.output().clone()).unwrap();
+        assert!(output.contains("Player 1 fires at (0, 0)"));
+        assert!(output.contains("Player 2 fires at"));
+        assert!(!output.contains("Pass the keyboard"));
+    }
+
+    #[test]
+    fn quitting_ends_the_game() {
+        let mut console = Console::new(Cursor::new("quit\n"), Vec::new());
+        assert!(play(&mut console, GameState::new(), &mut RandomShots::new(0)).unwrap().is_none());
+    }
+}
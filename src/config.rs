@@ -0,0 +1,374 @@
+use std::fmt::Display;
+use std::str::FromStr;
+use crate::error::GameError;
+use crate::fleet::FleetSpec;
+use crate::registry::ShipRegistry;
+
+pub const DEFAULT_GRID_WIDTH: u32 = 10;
+pub const DEFAULT_GRID_HEIGHT: u32 = 10;
+/// The longest a side of the grid may be.
+pub const MAX_GRID_SIZE: u32 = 100;
+/// The most ships a fleet may have, which keeps the search in `FleetSpec::fits` short.
+pub const MAX_FLEET_SHIPS: u32 = 30;
+
+/// Decides which player takes the first shot once both fleets have been placed.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FirstShooter {
+    Player1,
+    Random,
+    /// The player who lost the previous game shoots first. Falls back to Player 1
+    /// when there is no previous game (see `GameState::rematch`).
+    LoserOfLastGame,
+}
+
+impl Display for FirstShooter {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> std::result::Result<(), ::std::fmt::Error> {
+        match *self {
+            FirstShooter::Player1 => f.write_str("Player 1"),
+            FirstShooter::Random => f.write_str("Random"),
+            FirstShooter::LoserOfLastGame => f.write_str("Loser of last game"),
+        }
+    }
+}
+
+impl FromStr for FirstShooter {
+    type Err = GameError;
+
+    fn from_str(input: &str) -> Result<Self, GameError> {
+        match input {
+            "Player 1" => Ok(FirstShooter::Player1),
+            "Random" => Ok(FirstShooter::Random),
+            "Loser of last game" => Ok(FirstShooter::LoserOfLastGame),
+            _ => Err(GameError::parse(input, String::from("Unknown first shooter"))),
+        }
+    }
+}
+
+/// How many shots a player fires each turn.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ShotsPerTurn {
+    /// The standard rules, one shot and then the other player's turn.
+    #[default]
+    Single,
+    /// Salvo: as many shots as the player has ships afloat.
+    ShipsAfloat,
+    /// Salvo with the same number of shots every turn.
+    Fixed(u32),
+}
+
+impl Display for ShotsPerTurn {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> std::result::Result<(), ::std::fmt::Error> {
+        match *self {
+            ShotsPerTurn::Single => f.write_str("1"),
+            ShotsPerTurn::ShipsAfloat => f.write_str("ships"),
+            ShotsPerTurn::Fixed(shots) => write!(f, "{}", shots),
+        }
+    }
+}
+
+impl FromStr for ShotsPerTurn {
+    type Err = GameError;
+
+    fn from_str(input: &str) -> Result<Self, GameError> {
+        match input {
+            "1" => Ok(ShotsPerTurn::Single),
+            "ships" => Ok(ShotsPerTurn::ShipsAfloat),
+            count => match count.parse::<u32>() {
+                Ok(shots) if shots > 1 => Ok(ShotsPerTurn::Fixed(shots)),
+                _ => Err(GameError::parse(input, String::from("Expected ships or a number of shots"))),
+            },
+        }
+    }
+}
+
+/// Whether a shot that finds a ship earns the player another turn.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum KeepTurn {
+    /// The standard rules, the turn always passes to the other player.
+    #[default]
+    Never,
+    /// A hit earns another turn.
+    OnHit,
+    /// Only sinking a ship earns another turn.
+    OnSink,
+}
+
+impl Display for KeepTurn {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> std::result::Result<(), ::std::fmt::Error> {
+        match *self {
+            KeepTurn::Never => f.write_str("never"),
+            KeepTurn::OnHit => f.write_str("hit"),
+            KeepTurn::OnSink => f.write_str("sink"),
+        }
+    }
+}
+
+impl FromStr for KeepTurn {
+    type Err = GameError;
+
+    fn from_str(input: &str) -> Result<Self, GameError> {
+        match input {
+            "never" => Ok(KeepTurn::Never),
+            "hit" => Ok(KeepTurn::OnHit),
+            "sink" => Ok(KeepTurn::OnSink),
+            _ => Err(GameError::parse(input, String::from("Expected never, hit or sink"))),
+        }
+    }
+}
+
+/// Whether ships have to be placed with a gap between them.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NoTouch {
+    /// The standard rules, ships may touch as long as they don't overlap.
+    #[default]
+    Off,
+    /// Ships may not touch side by side, but may meet at a corner.
+    Orthogonal,
+    /// Ships may not meet at a corner, but may touch side by side.
+    Diagonal,
+    /// Ships may not touch at all.
+    All,
+}
+
+impl NoTouch {
+    /// The (row, col) offsets of the cells around a ship's cell that no other ship may cover.
+    pub fn neighbours(&self) -> &'static [(i64, i64)] {
+        const ORTHOGONAL: [(i64, i64); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+        const DIAGONAL: [(i64, i64); 4] = [(-1, -1), (-1, 1), (1, -1), (1, 1)];
+        const ALL: [(i64, i64); 8] = [(-1, 0), (1, 0), (0, -1), (0, 1), (-1, -1), (-1, 1), (1, -1), (1, 1)];
+        match self {
+            NoTouch::Off => &[],
+            NoTouch::Orthogonal => &ORTHOGONAL,
+            NoTouch::Diagonal => &DIAGONAL,
+            NoTouch::All => &ALL,
+        }
+    }
+}
+
+impl Display for NoTouch {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> std::result::Result<(), ::std::fmt::Error> {
+        match *self {
+            NoTouch::Off => f.write_str("off"),
+            NoTouch::Orthogonal => f.write_str("orthogonal"),
+            NoTouch::Diagonal => f.write_str("diagonal"),
+            NoTouch::All => f.write_str("all"),
+        }
+    }
+}
+
+impl FromStr for NoTouch {
+    type Err = GameError;
+
+    fn from_str(input: &str) -> Result<Self, GameError> {
+        match input {
+            "off" => Ok(NoTouch::Off),
+            "orthogonal" => Ok(NoTouch::Orthogonal),
+            "diagonal" => Ok(NoTouch::Diagonal),
+            "all" => Ok(NoTouch::All),
+            _ => Err(GameError::parse(input, String::from("Expected off, orthogonal, diagonal or all"))),
+        }
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameConfig {
+    pub first_shooter: FirstShooter,
+    /// Number of columns on each player's board.
+    pub width: u32,
+    /// Number of rows on each player's board.
+    pub height: u32,
+    /// The ship classes available to this game, including any custom ones.
+    pub registry: ShipRegistry,
+    /// The ships each player has to place.
+    pub fleet: FleetSpec,
+    /// How many shots a player fires each turn.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub shots_per_turn: ShotsPerTurn,
+    /// When a player fires again instead of passing the turn. Under the Salvo rules this
+    /// looks at the whole salvo.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub keep_turn: KeepTurn,
+    /// Which ways ships may not touch each other when they are placed.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub no_touch: NoTouch,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            first_shooter: FirstShooter::Player1,
+            width: DEFAULT_GRID_WIDTH,
+            height: DEFAULT_GRID_HEIGHT,
+            registry: ShipRegistry::default(),
+            fleet: FleetSpec::default(),
+            shots_per_turn: ShotsPerTurn::Single,
+            keep_turn: KeepTurn::Never,
+            no_touch: NoTouch::Off,
+        }
+    }
+}
+
+impl GameConfig {
+    /// Default rules on a `width` x `height` board.
+    pub fn with_grid(width: u32, height: u32) -> Self {
+        GameConfig { width, height, ..GameConfig::default() }
+    }
+
+    pub fn validate(&self) -> Result<(), GameError> {
+        if self.width == 0 || self.height == 0 {
+            return Err(GameError::InvalidConfig { reason: format!("Grid must be at least 1x1, found {}x{}", self.width, self.height) });
+        }
+        if self.width > MAX_GRID_SIZE || self.height > MAX_GRID_SIZE {
+            return Err(GameError::InvalidConfig { reason: format!("Grid can be at most {}x{}, found {}x{}", MAX_GRID_SIZE, MAX_GRID_SIZE, self.width, self.height) });
+        }
+        if self.fleet.ship_count() == 0 {
+            return Err(GameError::InvalidConfig { reason: String::from("Fleet must contain at least one ship") });
+        }
+        if self.fleet.ship_count() > MAX_FLEET_SHIPS {
+            return Err(GameError::InvalidConfig { reason: format!("Fleet can have at most {} ships, found {}", MAX_FLEET_SHIPS, self.fleet.ship_count()) });
+        }
+        if let Some((class, _)) = self.fleet.ships.iter().find(|(class, _)| !self.registry.contains(class)) {
+            return Err(GameError::InvalidConfig { reason: format!("Fleet uses {}, which is not in the ship registry", class) });
+        }
+        if !self.fleet.fits_with(self.width, self.height, self.no_touch) {
+            let apart = match self.no_touch {
+                NoTouch::Off => String::new(),
+                no_touch => format!(" with no-touch {}", no_touch),
+            };
+            return Err(GameError::InvalidConfig { reason: format!("Fleet of {} ships does not fit on a {}x{} grid{}", self.fleet.ship_count(), self.width, self.height, apart) });
+        }
+        if let ShotsPerTurn::Fixed(shots @ (0 | 1)) = self.shots_per_turn {
+            return Err(GameError::InvalidConfig { reason: format!("A salvo needs more than one shot, found {}", shots) });
+        }
+        Ok(())
+    }
+
+    /// Serializes the rules as `first shooter|<width>x<height>|fleet`, followed by `|` and the
+    /// optional rules as `name=value` pairs separated by `;` when any differ from the defaults.
+    /// The registry is left out, custom classes have to be stored alongside.
+    #[allow(clippy::inherent_to_string)]
+    pub fn to_string(&self) -> String {
+        let mut rules = Vec::new();
+        if self.shots_per_turn != ShotsPerTurn::Single {
+            rules.push(format!("shots={}", self.shots_per_turn));
+        }
+        if self.keep_turn != KeepTurn::Never {
+            rules.push(format!("again={}", self.keep_turn));
+        }
+        if self.no_touch != NoTouch::Off {
+            rules.push(format!("notouch={}", self.no_touch));
+        }
+        let config = format!("{}|{}x{}|{}", self.first_shooter, self.width, self.height, self.fleet.to_string());
+        if rules.is_empty() { config } else { format!("{}|{}", config, rules.join(";")) }
+    }
+
+    /// Parses rules serialized by `to_string`, resolving the fleet's classes through `registry`.
+    pub fn parse(input: &str, registry: &ShipRegistry) -> Result<Self, GameError> {
+        let parts = input.split('|').collect::<Vec<&str>>();
+        if parts.len() != 3 && parts.len() != 4 {
+            return Err(GameError::parse(input, format!("Expected config to contain 3 or 4 | separated values, found {}", parts.len())));
+        }
+        let (width, height) = match parts[1].split_once('x').map(|(w, h)| (w.parse::<u32>(), h.parse::<u32>())) {
+            Some((Ok(width), Ok(height))) => (width, height),
+            _ => return Err(GameError::parse(parts[1], String::from("Expected grid size as <width>x<height>"))),
+        };
+        let fleet = FleetSpec::parse(parts[2], registry)?;
+        let mut config = GameConfig { first_shooter: FirstShooter::from_str(parts[0])?, width, height, registry: registry.clone(), fleet, ..GameConfig::default() };
+        for rule in parts.get(3).into_iter().flat_map(|rules| rules.split(';')) {
+            match rule.split_once('=') {
+                Some(("shots", shots)) => config.shots_per_turn = ShotsPerTurn::from_str(shots)?,
+                Some(("again", again)) => config.keep_turn = KeepTurn::from_str(again)?,
+                Some(("notouch", no_touch)) => config.no_touch = NoTouch::from_str(no_touch)?,
+                _ => return Err(GameError::parse(rule, String::from("Unknown rule"))),
+            }
+        }
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{GameConfig, KeepTurn, NoTouch, ShotsPerTurn};
+    use crate::error::GameError;
+    use crate::fleet::FleetSpec;
+    use crate::registry::ShipRegistry;
+    use crate::ship::{ShipClass, ShipType};
+
+    #[test]
+    fn default_grid_is_10_by_10() {
+        let config = GameConfig::default();
+        assert_eq!(config.width, 10);
+        assert_eq!(config.height, 10);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn empty_grid_is_invalid() {
+        assert!(GameConfig::with_grid(0, 10).validate().is_err());
+        assert!(GameConfig::with_grid(8, 0).validate().is_err());
+        assert!(GameConfig::with_grid(15, 15).validate().is_ok());
+    }
+
+    #[test]
+    fn oversized_grid_is_invalid() {
+        assert!(GameConfig::with_grid(100, 100).validate().is_ok());
+        assert!(GameConfig::with_grid(101, 10).validate().is_err());
+        assert!(GameConfig::with_grid(70000, 70000).validate().is_err());
+        let config = GameConfig { fleet: FleetSpec::new(vec![(ShipType::TorpedoBoat, 31)]), ..GameConfig::default() };
+        assert!(config.validate().is_err());
+        let config = GameConfig { fleet: FleetSpec::new(vec![(ShipType::TorpedoBoat, 30)]), ..GameConfig::default() };
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn optional_rules_to_string_and_back() {
+        let config = GameConfig::default();
+        assert_eq!(config.to_string(), "Player 1|10x10|Battleship:1;Carrier:2;Submarine:2;Destroyer:2");
+        let config = GameConfig { shots_per_turn: ShotsPerTurn::ShipsAfloat, ..GameConfig::default() };
+        assert_eq!(config.to_string(), "Player 1|10x10|Battleship:1;Carrier:2;Submarine:2;Destroyer:2|shots=ships");
+        assert_eq!(GameConfig::parse(&config.to_string(), &ShipRegistry::standard()), Ok(config));
+        let config = GameConfig { shots_per_turn: ShotsPerTurn::Fixed(3), ..GameConfig::default() };
+        assert_eq!(GameConfig::parse(&config.to_string(), &ShipRegistry::standard()), Ok(config));
+        let config = GameConfig { shots_per_turn: ShotsPerTurn::Fixed(3), keep_turn: KeepTurn::OnSink, ..GameConfig::default() };
+        assert!(config.to_string().ends_with("|shots=3;again=sink"));
+        assert_eq!(GameConfig::parse(&config.to_string(), &ShipRegistry::standard()), Ok(config));
+        assert!(GameConfig::parse("Player 1|10x10|Destroyer:1|shots=0", &ShipRegistry::standard()).is_err());
+        assert!(GameConfig::parse("Player 1|10x10|Destroyer:1|again=miss", &ShipRegistry::standard()).is_err());
+        let config = GameConfig { no_touch: NoTouch::Diagonal, ..GameConfig::default() };
+        assert!(config.to_string().ends_with("|notouch=diagonal"));
+        assert_eq!(GameConfig::parse(&config.to_string(), &ShipRegistry::standard()), Ok(config));
+        assert!(GameConfig::parse("Player 1|10x10|Destroyer:1|colour=red", &ShipRegistry::standard()).is_err());
+    }
+
+    #[test]
+    fn fleet_must_fit_on_grid() {
+        let config = GameConfig { fleet: FleetSpec::classic(), ..GameConfig::with_grid(4, 4) };
+        assert!(config.validate().is_err());
+        let config = GameConfig { fleet: FleetSpec::russian(), ..GameConfig::with_grid(8, 8) };
+        assert!(config.validate().is_ok());
+        let config = GameConfig { fleet: FleetSpec::new(vec![]), ..GameConfig::default() };
+        assert!(config.validate().is_err());
+        // room for the cells but not for the gaps between ships
+        let config = GameConfig { fleet: FleetSpec::russian(), no_touch: NoTouch::All, ..GameConfig::with_grid(6, 6) };
+        assert!(matches!(config.validate(), Err(GameError::InvalidConfig { .. })));
+        assert!(GameConfig { no_touch: NoTouch::Off, ..config.clone() }.validate().is_ok());
+        assert!(GameConfig { width: 7, height: 7, ..config }.validate().is_ok());
+    }
+
+    #[test]
+    fn fleet_classes_must_be_registered() {
+        let mut registry = ShipRegistry::standard();
+        let dreadnought = registry.register(ShipClass::new("Dreadnought", 6)).unwrap();
+        let fleet = FleetSpec::new(vec![(dreadnought, 1)]);
+        let config = GameConfig { fleet: fleet.clone(), ..GameConfig::default() };
+        assert!(config.validate().is_err());
+        let config = GameConfig { registry, fleet, ..GameConfig::default() };
+        assert!(config.validate().is_ok());
+    }
+}
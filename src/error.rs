@@ -0,0 +1,131 @@
+use std::fmt::Display;
+use crate::game::{Direction, GameResult};
+use crate::location::Location;
+use crate::player::PlayerType;
+use crate::ship::ShipType;
+
+/// Reasons a game action or a deserialization can be rejected.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum GameError {
+    /// The player tried to act while it is the other player's turn.
+    NotYourTurn { player: PlayerType },
+    /// A shot was fired outside the grid.
+    OutOfBounds { location: Location, width: u32, height: u32 },
+    /// A ship placed at `location` would extend past the edge of the grid.
+    NoRoom { class: ShipType, location: Location, direction: Direction },
+    /// A salvo had a different number of shots than the player gets this turn.
+    WrongSalvoSize { expected: u32, found: u32 },
+    /// The player has already fired at this location.
+    AlreadyFired { location: Location },
+    /// A ship placed at `location` would overlap another ship.
+    Overlap { location: Location, direction: Direction },
+    /// A ship placed at `location` would touch the player's `touching` ship, which the
+    /// no-touch rule forbids.
+    Touching { location: Location, direction: Direction, touching: ShipType },
+    /// Every ship of this class has already been placed.
+    NoShipsOfClassLeft { class: ShipType },
+    /// There is no ship of this class covering the location.
+    ShipNotFound { class: ShipType, location: Location },
+    /// The player's unplaced ships cannot all fit in the room left on their board.
+    NoLayout { player: PlayerType },
+    /// The action is not allowed while the game is in its current phase.
+    WrongPhase { expected: GameResult, actual: GameResult },
+    /// A custom ship class definition was rejected by the registry.
+    InvalidShipClass { name: String, reason: String },
+    /// The game configuration cannot be played.
+    InvalidConfig { reason: String },
+    /// An event does not follow on from the game it was applied to.
+    InvalidEvent { event: String, reason: String },
+    /// A serialized value could not be read back.
+    ParseError { input: String, reason: String },
+}
+
+impl GameError {
+    pub fn parse(input: &str, reason: String) -> Self {
+        GameError::ParseError { input: String::from(input), reason }
+    }
+}
+
+impl Display for GameError {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> std::result::Result<(), ::std::fmt::Error> {
+        match self {
+            GameError::NotYourTurn { player } =>
+                write!(f, "{} cannot fire, it is not their turn.", player),
+            GameError::OutOfBounds { location, width, height } =>
+                write!(f, "Invalid fire coordinates {}, must be between {} and {}.", location, Location{row: 0, col: 0}, Location{row: height - 1, col: width - 1}),
+            GameError::NoRoom { class, location, direction } =>
+                write!(f, "Not enough room to place a {} at {} {}", class, location, direction),
+            GameError::WrongSalvoSize { expected, found } =>
+                write!(f, "Expected a salvo of {} shots, found {}.", expected, found),
+            GameError::AlreadyFired { location } =>
+                write!(f, "Cannot fire on {}, you have already fired there!", location),
+            GameError::Overlap { location, direction } =>
+                write!(f, "Cannot place a ship at {} {}, as it would overlap another ship.", location, direction),
+            GameError::Touching { location, direction, touching } =>
+                write!(f, "Cannot place a ship at {} {}, as it would touch your {}.", location, direction, touching),
+            GameError::NoShipsOfClassLeft { class } =>
+                write!(f, "There are no ships of class {} left to place", class),
+            GameError::ShipNotFound { class, location } =>
+                write!(f, "Could not find a {} at {}", class, location),
+            GameError::NoLayout { player } =>
+                write!(f, "There is no room left to place the rest of {}'s fleet", player),
+            GameError::WrongPhase { expected: GameResult::InSetup, .. } =>
+                f.write_str("Cannot place or remove ships after the game has started."),
+            GameError::WrongPhase { .. } =>
+                f.write_str("Cannot fire when game is not in progress"),
+            GameError::InvalidShipClass { name, reason } =>
+                write!(f, "Invalid ship class \"{}\": {}", name, reason),
+            GameError::InvalidConfig { reason } =>
+                write!(f, "Invalid game configuration: {}", reason),
+            GameError::InvalidEvent { event, reason } =>
+                write!(f, "Cannot apply event \"{}\": {}", event, reason),
+            GameError::ParseError { input, reason } =>
+                write!(f, "Could not parse \"{}\": {}", input, reason),
+        }
+    }
+}
+
+impl std::error::Error for GameError {}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use crate::error::GameError;
+    use crate::game::{Direction, GameResult};
+    use crate::location::Location;
+    use crate::player::PlayerType;
+    use crate::ship::ShipType;
+
+    #[test]
+    fn display_describes_the_error() {
+        let error = GameError::NotYourTurn { player: PlayerType::Player2 };
+        assert_eq!(error.to_string(), "Player 2 cannot fire, it is not their turn.");
+
+        let error = GameError::OutOfBounds { location: Location{row: 10, col: 0}, width: 10, height: 10 };
+        assert_eq!(error.to_string(), "Invalid fire coordinates (0, 10), must be between (0, 0) and (9, 9).");
+
+        let error = GameError::NoRoom { class: ShipType::Carrier, location: Location{row: 0, col: 8}, direction: Direction::Right };
+        assert_eq!(error.to_string(), "Not enough room to place a Carrier at (8, 0) Right");
+
+        let error = GameError::Touching { location: Location{row: 1, col: 0}, direction: Direction::Right, touching: ShipType::Carrier };
+        assert_eq!(error.to_string(), "Cannot place a ship at (0, 1) Right, as it would touch your Carrier.");
+
+        let error = GameError::WrongPhase { expected: GameResult::InProgress, actual: GameResult::InSetup };
+        assert_eq!(error.to_string(), "Cannot fire when game is not in progress");
+    }
+
+    #[test]
+    fn parse_errors_keep_the_input() {
+        let error = Location::from_str("a,1").err().unwrap();
+        assert!(matches!(error, GameError::ParseError { ref input, .. } if input == "a,1"));
+
+        let error = ShipType::from_str("Rowboat").err().unwrap();
+        assert_eq!(error, GameError::ParseError { input: String::from("Rowboat"), reason: String::from("Unknown ship class") });
+    }
+
+    #[test]
+    fn is_a_std_error() {
+        let error: Box<dyn std::error::Error> = Box::new(GameError::AlreadyFired { location: Location{row: 1, col: 2} });
+        assert_eq!(error.to_string(), "Cannot fire on (2, 1), you have already fired there!");
+    }
+}
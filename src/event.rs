@@ -0,0 +1,178 @@
+use std::str::FromStr;
+use crate::config::GameConfig;
+use crate::error::GameError;
+use crate::game::Direction;
+use crate::location;
+use crate::location::Location;
+use crate::player::PlayerType;
+use crate::registry::ShipRegistry;
+use crate::ship::ShipType;
+use crate::shot::Shot;
+
+/// Something that happened in a game. Every accepted action is appended to the game's log
+/// (`GameState::events`), and folding the log with `GameState::from_events` rebuilds the game.
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GameEvent {
+    /// A game was set up with these rules. `last_loser` is who lost the game before a rematch.
+    Created { config: GameConfig, last_loser: Option<PlayerType> },
+    ShipPlaced { player: PlayerType, class: ShipType, location: Location, direction: Direction },
+    ShipRemoved { player: PlayerType, class: ShipType, location: Location },
+    /// Both fleets were placed and the game moved on to firing.
+    GameStarted { first: PlayerType },
+    /// `sunk` is the class of the ship the shot sank, if it sank one.
+    ShotFired { player: PlayerType, location: Location, hit: bool, sunk: Option<ShipType> },
+    /// A turn's shots under the Salvo rules, all resolved together. `sunk` holds the classes of
+    /// the ships the salvo sank.
+    SalvoFired { player: PlayerType, shots: Vec<Shot>, sunk: Vec<ShipType> },
+    GameOver { winner: PlayerType },
+}
+
+impl GameEvent {
+    /// Serializes the event as `|` separated fields, starting with the kind of event.
+    #[allow(clippy::inherent_to_string)]
+    pub fn to_string(&self) -> String {
+        match self {
+            GameEvent::Created { config, last_loser } =>
+                format!("created|{}|{}", last_loser.map_or(String::from("None"), |player| player.to_string()), config.to_string()),
+            GameEvent::ShipPlaced { player, class, location, direction } =>
+                format!("placed|{}|{}|{}|{}", player, class, location.to_string(), direction),
+            GameEvent::ShipRemoved { player, class, location } =>
+                format!("removed|{}|{}|{}", player, class, location.to_string()),
+            GameEvent::GameStarted { first } => format!("started|{}", first),
+            GameEvent::ShotFired { player, location, hit, sunk } =>
+                format!("fired|{}|{}|{}|{}", player, location.to_string(), hit, sunk.as_ref().map_or(String::from("None"), |class| class.to_string())),
+            GameEvent::SalvoFired { player, shots, sunk } => {
+                let locations: Vec<Location> = shots.iter().map(|shot| shot.location).collect();
+                let hits: Vec<String> = shots.iter().map(|shot| shot.hit.to_string()).collect();
+                let sunk: Vec<String> = sunk.iter().map(|class| class.to_string()).collect();
+                format!("salvo|{}|{}|{}|{}", player, location::locations_to_string(&locations), hits.join(";"), sunk.join(";"))
+            }
+            GameEvent::GameOver { winner } => format!("over|{}", winner),
+        }
+    }
+
+    /// Parses an event serialized by `to_string`, resolving ship classes through `registry`.
+    pub fn parse(input: &str, registry: &ShipRegistry) -> Result<Self, GameError> {
+        let (kind, fields) = input.split_once('|').unwrap_or((input, ""));
+        let parts: Vec<&str> = fields.split('|').collect();
+        let expect = |count: usize| match parts.len() == count {
+            true => Ok(()),
+            false => Err(GameError::parse(input, format!("Expected {} event to contain {} | separated values, found {}", kind, count, parts.len()))),
+        };
+        match kind {
+            "created" => {
+                let (last_loser, config) = fields.split_once('|').ok_or_else(|| GameError::parse(input, String::from("Expected created event to contain a config")))?;
+                let last_loser = match last_loser {
+                    "None" => None,
+                    player => Some(PlayerType::from_str(player)?),
+                };
+                Ok(GameEvent::Created { config: GameConfig::parse(config, registry)?, last_loser })
+            }
+            "placed" => {
+                expect(4)?;
+                Ok(GameEvent::ShipPlaced {
+                    player: PlayerType::from_str(parts[0])?,
+                    class: registry.parse(parts[1])?,
+                    location: Location::from_str(parts[2])?,
+                    direction: Direction::from_str(parts[3])?,
+                })
+            }
+            "removed" => {
+                expect(3)?;
+                Ok(GameEvent::ShipRemoved { player: PlayerType::from_str(parts[0])?, class: registry.parse(parts[1])?, location: Location::from_str(parts[2])? })
+            }
+            "started" => {
+                expect(1)?;
+                Ok(GameEvent::GameStarted { first: PlayerType::from_str(parts[0])? })
+            }
+            "fired" => {
+                expect(4)?;
+                let hit = parse_hit(parts[2])?;
+                let sunk = match parts[3] {
+                    "None" => None,
+                    class => Some(registry.parse(class)?),
+                };
+                Ok(GameEvent::ShotFired { player: PlayerType::from_str(parts[0])?, location: Location::from_str(parts[1])?, hit, sunk })
+            }
+            "salvo" => {
+                expect(4)?;
+                let locations = location::locations_from_string(parts[1])?;
+                let hits = parts[2].split(';').map(parse_hit).collect::<Result<Vec<bool>, GameError>>()?;
+                if hits.len() != locations.len() {
+                    return Err(GameError::parse(input, format!("Expected {} hits, found {}", locations.len(), hits.len())));
+                }
+                let sunk = match parts[3] {
+                    "" => Vec::new(),
+                    sunk => sunk.split(';').map(|class| registry.parse(class)).collect::<Result<_, _>>()?,
+                };
+                let shots = locations.into_iter().zip(hits).map(|(location, hit)| Shot { location, hit }).collect();
+                Ok(GameEvent::SalvoFired { player: PlayerType::from_str(parts[0])?, shots, sunk })
+            }
+            "over" => {
+                expect(1)?;
+                Ok(GameEvent::GameOver { winner: PlayerType::from_str(parts[0])? })
+            }
+            _ => Err(GameError::parse(input, format!("Unknown event {}", kind))),
+        }
+    }
+}
+
+fn parse_hit(input: &str) -> Result<bool, GameError> {
+    match input {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        other => Err(GameError::parse(other, String::from("Expected true or false"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::GameConfig;
+    use crate::event::GameEvent;
+    use crate::fleet::FleetSpec;
+    use crate::game::Direction;
+    use crate::location::Location;
+    use crate::player::PlayerType;
+    use crate::registry::ShipRegistry;
+    use crate::ship::{ShipClass, ShipType};
+    use crate::shot::Shot;
+
+    #[test]
+    fn events_to_string_and_back() {
+        let mut registry = ShipRegistry::standard();
+        let raft = registry.register(ShipClass::new("Raft", 1)).unwrap();
+        let config = GameConfig { registry: registry.clone(), fleet: FleetSpec::new(vec![(raft.clone(), 2)]), ..GameConfig::with_grid(4, 3) };
+        let events = [
+            GameEvent::Created { config, last_loser: Some(PlayerType::Player2) },
+            GameEvent::ShipPlaced { player: PlayerType::Player1, class: raft.clone(), location: Location { row: 2, col: 1 }, direction: Direction::Left },
+            GameEvent::ShipRemoved { player: PlayerType::Player1, class: raft.clone(), location: Location { row: 2, col: 1 } },
+            GameEvent::GameStarted { first: PlayerType::Player2 },
+            GameEvent::ShotFired { player: PlayerType::Player2, location: Location { row: 0, col: 3 }, hit: false, sunk: None },
+            GameEvent::ShotFired { player: PlayerType::Player1, location: Location { row: 1, col: 1 }, hit: true, sunk: Some(raft.clone()) },
+            GameEvent::SalvoFired { player: PlayerType::Player2, shots: vec![Shot { location: Location { row: 0, col: 0 }, hit: false }], sunk: Vec::new() },
+            GameEvent::SalvoFired {
+                player: PlayerType::Player1,
+                shots: vec![Shot { location: Location { row: 2, col: 0 }, hit: true }, Shot { location: Location { row: 2, col: 3 }, hit: false }],
+                sunk: vec![raft],
+            },
+            GameEvent::GameOver { winner: PlayerType::Player1 },
+        ];
+        for event in events {
+            assert_eq!(GameEvent::parse(&event.to_string(), &registry), Ok(event));
+        }
+        assert_eq!(
+            GameEvent::ShotFired { player: PlayerType::Player1, location: Location { row: 1, col: 2 }, hit: true, sunk: Some(ShipType::TorpedoBoat) }.to_string(),
+            "fired|Player 1|2,1|true|Torpedo Boat",
+        );
+    }
+
+    #[test]
+    fn bad_events_are_rejected() {
+        let registry = ShipRegistry::standard();
+        assert!(GameEvent::parse("exploded|Player 1", &registry).is_err());
+        assert!(GameEvent::parse("placed|Player 1|Carrier|0,0", &registry).is_err());
+        assert!(GameEvent::parse("fired|Player 1|0,0|maybe|None", &registry).is_err());
+        assert!(GameEvent::parse("over|Player 3", &registry).is_err());
+    }
+}
@@ -0,0 +1,343 @@
+use crate::config::{NoTouch, MAX_FLEET_SHIPS, MAX_GRID_SIZE};
+use crate::error::GameError;
+use crate::game::Direction;
+use crate::location::Location;
+use crate::registry::ShipRegistry;
+use crate::ship::{Ship, ShipType};
+
+// (row, col) offsets of the cells a ship covers in one orientation
+type Footprint = Vec<(i64, i64)>;
+
+/// The ship classes each player has to place, and how many of each.
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FleetSpec {
+    pub ships: Vec<(ShipType, u32)>,
+}
+
+impl Default for FleetSpec {
+    fn default() -> Self {
+        FleetSpec::original()
+    }
+}
+
+impl FleetSpec {
+    pub fn new(ships: Vec<(ShipType, u32)>) -> Self {
+        FleetSpec { ships }
+    }
+
+    /// The Hasbro fleet: one each of Carrier, Battleship, Cruiser, Submarine and Destroyer.
+    pub fn classic() -> Self {
+        FleetSpec::new(vec![
+            (ShipType::Carrier, 1),
+            (ShipType::Battleship, 1),
+            (ShipType::Cruiser, 1),
+            (ShipType::Submarine, 1),
+            (ShipType::Destroyer, 1),
+        ])
+    }
+
+    /// The fleet this game has always been played with.
+    pub fn original() -> Self {
+        FleetSpec::new(vec![
+            (ShipType::Battleship, 1),
+            (ShipType::Carrier, 2),
+            (ShipType::Submarine, 2),
+            (ShipType::Destroyer, 2),
+        ])
+    }
+
+    /// The Russian variant: one ship of 4, two of 3, three of 2 and four of 1.
+    pub fn russian() -> Self {
+        FleetSpec::new(vec![
+            (ShipType::Battleship, 1),
+            (ShipType::Cruiser, 2),
+            (ShipType::Destroyer, 3),
+            (ShipType::TorpedoBoat, 4),
+        ])
+    }
+
+    /// Looks up a preset by name: `classic`, `original` or `russian`.
+    pub fn preset(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "classic" => Some(FleetSpec::classic()),
+            "original" => Some(FleetSpec::original()),
+            "russian" => Some(FleetSpec::russian()),
+            _ => None,
+        }
+    }
+
+    /// Unplaced ships for one player, in the order they are listed.
+    pub fn build(&self) -> Vec<Ship> {
+        let mut ships: Vec<Ship> = Vec::new();
+        for (class, count) in self.ships.iter() {
+            for _ in 0..*count {
+                ships.push(Ship::new(class.clone()));
+            }
+        }
+        ships
+    }
+
+    /// Number of ships in the fleet, saturating at `u32::MAX` for absurd counts.
+    pub fn ship_count(&self) -> u32 {
+        self.ships.iter().fold(0u32, |total, (_, count)| total.saturating_add(*count))
+    }
+
+    /// Number of grid cells the whole fleet covers, saturating at `u32::MAX`.
+    pub fn cells(&self) -> u32 {
+        self.ships.iter().fold(0u32, |total, (class, count)| total.saturating_add(class.size().saturating_mul(*count)))
+    }
+
+    /// Returns true if there is at least one layout that places every ship on a
+    /// `width` x `height` grid without overlapping.
+    pub fn fits(&self, width: u32, height: u32) -> bool {
+        self.fits_with(width, height, NoTouch::Off)
+    }
+
+    /// Like `fits`, but the ships also have to keep apart as `no_touch` requires. The search
+    /// gives up after a fixed number of placements, so a fleet that only just fits may be
+    /// reported as not fitting. Grids over `MAX_GRID_SIZE` and fleets over `MAX_FLEET_SHIPS`
+    /// never fit.
+    pub fn fits_with(&self, width: u32, height: u32, no_touch: NoTouch) -> bool {
+        if width > MAX_GRID_SIZE || height > MAX_GRID_SIZE || self.ship_count() > MAX_FLEET_SHIPS {
+            return false;
+        }
+        // u64 so large grids and fleets can't overflow, `cells` saturates too early for this
+        let area = width as u64 * height as u64;
+        let cells: u64 = self.ships.iter().map(|(class, count)| class.size() as u64 * *count as u64).sum();
+        if cells > area {
+            return false;
+        }
+        let classes: Vec<ShipType> = self.build().into_iter().map(|ship| ship.class).collect();
+        find_layout(&classes, &[], width, height, no_touch).is_some()
+    }
+
+    /// Serializes as `class:count` pairs separated by `;`.
+    #[allow(clippy::inherent_to_string)]
+    pub fn to_string(&self) -> String {
+        self.ships.iter().map(|(class, count)| format!("{}:{}", class, count)).collect::<Vec<String>>().join(";")
+    }
+
+    /// Reads a fleet serialized by `to_string`, resolving class names through `registry`.
+    pub fn parse(input: &str, registry: &ShipRegistry) -> Result<Self, GameError> {
+        let mut ships: Vec<(ShipType, u32)> = Vec::new();
+        for entry in input.split(';').filter(|entry| !entry.is_empty()) {
+            let (name, count) = match entry.split_once(':') {
+                Some(parts) => parts,
+                None => return Err(GameError::parse(entry, String::from("Expected class:count"))),
+            };
+            let count = match count.parse::<u32>() {
+                Ok(count) => count,
+                Err(_) => return Err(GameError::parse(entry, format!("Could not parse count, expected u32, found {}", count))),
+            };
+            ships.push((registry.parse(name)?, count));
+        }
+        Ok(FleetSpec::new(ships))
+    }
+}
+
+// one way round a ship can lie: the direction it faces, where its bow is from the top left
+// corner of the cells it covers, and those cells from the same corner
+struct Orientation {
+    direction: Direction,
+    bow: (i64, i64),
+    cells: Footprint,
+}
+
+// the distinct footprints of a class in each direction, shifted so no offset is negative
+fn orientations(class: &ShipType) -> Vec<Orientation> {
+    let mut orientations: Vec<Orientation> = Vec::new();
+    for direction in [Direction::Right, Direction::Down, Direction::Left, Direction::Up] {
+        let footprint = class.footprint(direction);
+        let min_row = footprint.iter().map(|(row, _)| *row).min().unwrap_or(0);
+        let min_col = footprint.iter().map(|(_, col)| *col).min().unwrap_or(0);
+        let mut cells: Footprint = footprint.iter().map(|(row, col)| (row - min_row, col - min_col)).collect();
+        cells.sort();
+        if !orientations.iter().any(|orientation| orientation.cells == cells) {
+            orientations.push(Orientation { direction, bow: (-min_row, -min_col), cells });
+        }
+    }
+    orientations
+}
+
+// how many placements `find_layout` tries before giving up
+const MAX_FIT_ATTEMPTS: usize = 1_000_000;
+
+/// Searches for a place for each of `classes` on a `width` x `height` grid where the `taken`
+/// cells already hold ships, following the `no_touch` rule, and returns where each ship's bow
+/// goes and which way it faces. The search is deterministic and gives up after a fixed number
+/// of placements, it is what `FleetSpec::fits_with` runs on an empty grid.
+pub(crate) fn find_layout(classes: &[ShipType], taken: &[Location], width: u32, height: u32, no_touch: NoTouch) -> Option<Vec<(ShipType, Location, Direction)>> {
+    let mut classes = classes.to_vec();
+    // the biggest ships are the hardest to fit, so try them first
+    classes.sort_by_key(|class| std::cmp::Reverse(class.size()));
+    let ships: Vec<(ShipType, Vec<Orientation>)> = classes.into_iter()
+        .map(|class| { let orientations = orientations(&class); (class, orientations) })
+        .collect();
+    let mut grid = vec![false; width as usize * height as usize];
+    for location in taken.iter().filter(|location| location.row < height && location.col < width) {
+        grid[(location.row * width + location.col) as usize] = true;
+    }
+    let mut layout = Layout { grid, width, height, neighbours: no_touch.neighbours(), attempts: 0, placed: Vec::new() };
+    if fit_from(&ships, 0, 0, &mut layout) { Some(layout.placed) } else { None }
+}
+
+// the grid a layout is being searched for on, true where a ship is
+struct Layout {
+    grid: Vec<bool>,
+    width: u32,
+    height: u32,
+    // offsets around a ship's cells that no other ship may cover
+    neighbours: &'static [(i64, i64)],
+    attempts: usize,
+    // the ships placed so far on the way down the search
+    placed: Vec<(ShipType, Location, Direction)>,
+}
+
+impl Layout {
+    // true if a ship could cover (row, col), it is free and so are the neighbours the
+    // no-touch rule keeps clear
+    fn open(&self, row: i64, col: i64) -> bool {
+        std::iter::once(&(0, 0)).chain(self.neighbours.iter()).all(|(r, c)| {
+            let (row, col) = (row + r, col + c);
+            row < 0 || col < 0 || row >= self.height as i64 || col >= self.width as i64
+                || !self.grid[(row * self.width as i64 + col) as usize]
+        })
+    }
+}
+
+// depth-first search for a layout of ships[index..]; `first` is the lowest start cell to try,
+// which stops identical ships from being tried in every permutation
+fn fit_from(ships: &[(ShipType, Vec<Orientation>)], index: usize, first: usize, layout: &mut Layout) -> bool {
+    if index == ships.len() {
+        return true;
+    }
+    let (class, orientations) = &ships[index];
+    let (width, height) = (layout.width as i64, layout.height as i64);
+    for start in first..layout.grid.len() {
+        let row = start as i64 / width;
+        let col = start as i64 % width;
+        for orientation in orientations {
+            if layout.attempts == MAX_FIT_ATTEMPTS {
+                return false;
+            }
+            layout.attempts += 1;
+            if orientation.cells.iter().any(|(r, c)| row + r >= height || col + c >= width || !layout.open(row + r, col + c)) {
+                continue;
+            }
+            let cells: Vec<usize> = orientation.cells.iter()
+                .map(|(r, c)| ((row + r) * width + col + c) as usize)
+                .collect();
+            cells.iter().for_each(|cell| layout.grid[*cell] = true);
+            let bow = Location { row: (row + orientation.bow.0) as u32, col: (col + orientation.bow.1) as u32 };
+            layout.placed.push((class.clone(), bow, orientation.direction));
+            let next_first = if index + 1 < ships.len() && &ships[index + 1].0 == class { start } else { 0 };
+            if fit_from(ships, index + 1, next_first, layout) {
+                return true;
+            }
+            layout.placed.pop();
+            cells.iter().for_each(|cell| layout.grid[*cell] = false);
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::NoTouch;
+    use crate::fleet::FleetSpec;
+    use crate::location::Location;
+    use crate::registry::ShipRegistry;
+    use crate::ship::{ShipClass, ShipType};
+
+    #[test]
+    fn presets() {
+        assert_eq!(FleetSpec::classic().ship_count(), 5);
+        assert_eq!(FleetSpec::classic().cells(), 16);
+        assert_eq!(FleetSpec::original().ship_count(), 7);
+        assert_eq!(FleetSpec::russian().ship_count(), 10);
+        assert_eq!(FleetSpec::russian().cells(), 20);
+        assert_eq!(FleetSpec::preset("Russian"), Some(FleetSpec::russian()));
+        assert_eq!(FleetSpec::preset("armada"), None);
+        assert!(FleetSpec::classic().build().iter().any(|ship| ship.class == ShipType::Cruiser));
+    }
+
+    #[test]
+    fn build_expands_counts() {
+        let fleet = FleetSpec::new(vec![(ShipType::Carrier, 1), (ShipType::Destroyer, 2)]);
+        let classes: Vec<ShipType> = fleet.build().into_iter().map(|ship| ship.class).collect();
+        assert_eq!(classes, vec![ShipType::Carrier, ShipType::Destroyer, ShipType::Destroyer]);
+        assert!(fleet.build().iter().all(|ship| ship.locations.is_empty()));
+    }
+
+    #[test]
+    fn presets_fit_on_standard_grid() {
+        assert!(FleetSpec::classic().fits(10, 10));
+        assert!(FleetSpec::original().fits(10, 10));
+        assert!(FleetSpec::russian().fits(10, 10));
+        assert!(FleetSpec::classic().fits(8, 8));
+    }
+
+    #[test]
+    fn fleet_that_does_not_fit() {
+        // a carrier is longer than the grid
+        assert!(!FleetSpec::classic().fits(4, 4));
+        // more cells than the grid has
+        assert!(!FleetSpec::new(vec![(ShipType::Destroyer, 5)]).fits(3, 3));
+        // enough cells, but carriers fill whole rows, leaving two rows for three cruisers
+        assert!(!FleetSpec::new(vec![(ShipType::Carrier, 3), (ShipType::Cruiser, 3)]).fits(5, 5));
+        assert!(FleetSpec::new(vec![(ShipType::Carrier, 3), (ShipType::Cruiser, 2)]).fits(5, 5));
+        // counts and grids too big for u32 arithmetic
+        let huge = FleetSpec::new(vec![(ShipType::Carrier, u32::MAX), (ShipType::Destroyer, u32::MAX)]);
+        assert_eq!(huge.ship_count(), u32::MAX);
+        assert_eq!(huge.cells(), u32::MAX);
+        assert!(!huge.fits(70000, 70000));
+        // past the limits a game can be played with
+        assert!(!FleetSpec::new(vec![(ShipType::TorpedoBoat, 31)]).fits(100, 100));
+        assert!(!FleetSpec::new(vec![(ShipType::TorpedoBoat, 1)]).fits(101, 1));
+    }
+
+    #[test]
+    fn no_touch_needs_more_room() {
+        let destroyers = FleetSpec::new(vec![(ShipType::Destroyer, 2)]);
+        assert!(destroyers.fits_with(2, 2, NoTouch::Off));
+        assert!(!destroyers.fits_with(2, 2, NoTouch::Orthogonal));
+        assert!(!destroyers.fits_with(2, 2, NoTouch::Diagonal));
+        // one in the top row and one in the bottom
+        assert!(destroyers.fits_with(2, 3, NoTouch::All));
+        let three = FleetSpec::new(vec![(ShipType::Destroyer, 3)]);
+        assert!(three.fits_with(2, 3, NoTouch::Off));
+        assert!(!three.fits_with(2, 3, NoTouch::All));
+        assert!(FleetSpec::russian().fits_with(10, 10, NoTouch::All));
+        // cells enough for the Russian fleet but no room to keep it apart, found out quickly
+        assert!(FleetSpec::russian().fits(5, 5));
+        assert!(!FleetSpec::russian().fits_with(5, 5, NoTouch::All));
+    }
+
+    #[test]
+    fn custom_classes_in_fleet() {
+        let mut registry = ShipRegistry::standard();
+        let dreadnought = registry.register(ShipClass::new("Dreadnought", 6)).unwrap();
+        let fleet = FleetSpec::new(vec![(dreadnought.clone(), 1), (ShipType::Carrier, 1)]);
+        assert_eq!(fleet.cells(), 11);
+        assert!(fleet.fits(6, 2));
+        assert!(!fleet.fits(5, 5));
+
+        // an L shape needs a 2x2 corner
+        let corner = registry.register(ShipClass::shaped("Corner", vec![Location{row: 0, col: 0}, Location{row: 0, col: 1}, Location{row: 1, col: 0}])).unwrap();
+        assert!(FleetSpec::new(vec![(corner.clone(), 1)]).fits(2, 2));
+        assert!(!FleetSpec::new(vec![(corner, 1)]).fits(3, 1));
+    }
+
+    #[test]
+    fn fleet_to_string_and_back() {
+        let mut registry = ShipRegistry::standard();
+        registry.register(ShipClass::new("Patrol Boat", 1)).unwrap();
+        let fleet = FleetSpec::parse("Carrier:1;Patrol Boat:3", &registry).unwrap();
+        assert_eq!(fleet.ship_count(), 4);
+        assert_eq!(fleet.to_string(), "Carrier:1;Patrol Boat:3");
+        assert_eq!(FleetSpec::parse(&FleetSpec::russian().to_string(), &registry).unwrap(), FleetSpec::russian());
+        assert!(FleetSpec::parse("Carrier", &registry).is_err());
+        assert!(FleetSpec::parse("Rowboat:1", &registry).is_err());
+    }
+}
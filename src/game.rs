@@ -1,33 +1,76 @@
-use std::fmt::{Display, Formatter};
+use std::fmt::Display;
+use std::str::FromStr;
+use rand::Rng;
+use crate::config::{FirstShooter, GameConfig, KeepTurn, ShotsPerTurn};
+use crate::error::GameError;
+use crate::event::GameEvent;
 use crate::location::Location;
+use crate::placer;
 use crate::player::{PlayerTurn, PlayerType};
-use crate::ship::{Ship, ShipType};
-use crate::shot::Shot;
+use crate::registry::ShipRegistry;
+use crate::ship::{Ship, ShipClass, ShipType, ships_from_str, ships_to_string};
+use crate::shot::{Shot, shots_from_str, shots_to_string};
 
-const GRID_WIDTH: u32 = 10;
-const GRID_HEIGHT: u32 = 10;
+// bumped whenever the serialized game format changes, version 1 had no event records
+const FORMAT_VERSION: u32 = 2;
 
-enum EnemyBoardCell {
+/// A cell of the opponent's board, as far as the player looking at it knows.
+#[derive(Eq,PartialEq,Copy,Clone,Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EnemyBoardCell {
     Empty,
     Hit,
     Miss,
+    /// A hit on a ship that has since been sunk.
+    Sunk,
 }
 
-enum OwnBoardCell {
+/// A cell of the player's own board.
+#[derive(Eq,PartialEq,Copy,Clone,Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OwnBoardCell {
     Empty,
     Ship,
     ShipHit,
+    /// The opponent fired here and missed.
+    Miss,
 }
 
-#[derive(Eq,PartialEq,Clone)]
-enum GameResult {
+#[derive(Eq,PartialEq,Copy,Clone,Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GameResult {
     InSetup,
     InProgress,
     Player1Win,
     Player2Win
 }
+impl Display for GameResult {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::result::Result<(), ::std::fmt::Error> {
+        match *self {
+            GameResult::InSetup => f.write_str("In Setup"),
+            GameResult::InProgress => f.write_str("In Progress"),
+            GameResult::Player1Win => f.write_str("Player 1 Win"),
+            GameResult::Player2Win => f.write_str("Player 2 Win"),
+        }
+    }
+}
+
+impl FromStr for GameResult {
+    type Err = GameError;
+
+    fn from_str(input: &str) -> Result<Self, GameError> {
+        match input {
+            "In Setup" => Ok(GameResult::InSetup),
+            "In Progress" => Ok(GameResult::InProgress),
+            "Player 1 Win" => Ok(GameResult::Player1Win),
+            "Player 2 Win" => Ok(GameResult::Player2Win),
+            _ => Err(GameError::parse(input, String::from("Unknown game result"))),
+        }
+    }
+}
+
 impl GameResult {
-    fn player_win(player: &PlayerType) -> Self {
+    pub fn player_win(player: &PlayerType) -> Self {
         match player {
             PlayerType::Player1 => GameResult::Player1Win,
             PlayerType::Player2 => GameResult::Player2Win
@@ -35,8 +78,10 @@ impl GameResult {
     }
 }
 
-#[derive(Copy,Clone)]
-enum Direction {
+/// The direction a ship extends in from the location it is placed at.
+#[derive(Eq,PartialEq,Copy,Clone,Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Direction {
     Up,
     Down,
     Left,
@@ -53,7 +98,22 @@ impl Display for Direction {
     }
 }
 
-#[derive(Clone)]
+impl FromStr for Direction {
+    type Err = GameError;
+
+    fn from_str(input: &str) -> Result<Self, GameError> {
+        match input.to_lowercase().as_str() {
+            "up" => Ok(Direction::Up),
+            "down" => Ok(Direction::Down),
+            "left" => Ok(Direction::Left),
+            "right" => Ok(Direction::Right),
+            _ => Err(GameError::parse(input, String::from("Expected Up, Down, Left or Right"))),
+        }
+    }
+}
+
+#[derive(Clone,Eq,PartialEq,Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GameState {
     p1_ships: Vec<Ship>,
     p1_shots: Vec<Shot>,
@@ -62,42 +122,70 @@ pub struct GameState {
     result: GameResult,
     turn: PlayerTurn,
     messages: Vec<String>,
+    config: GameConfig,
+    last_loser: Option<PlayerType>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    events: Vec<GameEvent>,
+}
+
+impl Default for GameState {
+    fn default() -> Self {
+        GameState::new()
+    }
 }
 
 impl GameState {
     pub fn new() -> Self {
-        return GameState {
-            p1_ships: vec!(
-                Ship::battleship(),
-                Ship::carrier(),
-                Ship::carrier(),
-                Ship::submarine(),
-                Ship::submarine(),
-                Ship::destroyer(),
-                Ship::destroyer()),
+        GameState::setup(GameConfig::default(), None)
+    }
+
+    pub fn with_config(config: GameConfig) -> Result<Self, GameError> {
+        config.validate()?;
+        Ok(GameState::setup(config, None))
+    }
+
+    /// Rebuilds a game by applying `events` in order, starting from `GameState::new()`.
+    pub fn from_events(events: &[GameEvent]) -> Result<Self, GameError> {
+        let mut game = GameState::new();
+        for event in events {
+            apply(&mut game, event.clone())?;
+        }
+        Ok(game)
+    }
+
+    fn setup(config: GameConfig, last_loser: Option<PlayerType>) -> Self {
+        GameState {
+            events: vec![GameEvent::Created { config: config.clone(), last_loser }],
+            p1_ships: config.fleet.build(),
             p1_shots: Vec::new(),
-            p2_ships: vec!(
-                Ship::battleship(),
-                Ship::carrier(),
-                Ship::carrier(),
-                Ship::submarine(),
-                Ship::submarine(),
-                Ship::destroyer(),
-                Ship::destroyer()),
+            p2_ships: config.fleet.build(),
             p2_shots: Vec::new(),
             result: GameResult::InSetup,
             turn: PlayerTurn::Either,
             messages: vec![String::from("Players, please place your ships to begin the game!")],
+            config,
+            last_loser,
         }
     }
 
+    /// Starts a fresh game with the same configuration, remembering who lost this one
+    /// so that `FirstShooter::LoserOfLastGame` can hand them the first shot.
+    pub fn rematch(&self) -> Self {
+        let last_loser = match self.result {
+            GameResult::Player1Win => Some(PlayerType::Player2),
+            GameResult::Player2Win => Some(PlayerType::Player1),
+            _ => self.last_loser,
+        };
+        GameState::setup(self.config.clone(), last_loser)
+    }
+
     pub fn shots(&self, player: &PlayerType) -> &Vec<Shot> {
         match player {
             PlayerType::Player1 => &self.p1_shots,
             PlayerType::Player2 => &self.p2_shots
         }
     }
-    pub fn shots_mut(&mut self, player: &PlayerType) -> &mut Vec<Shot> {
+    pub(crate) fn shots_mut(&mut self, player: &PlayerType) -> &mut Vec<Shot> {
         match player {
             PlayerType::Player1 => &mut self.p1_shots,
             PlayerType::Player2 => &mut self.p2_shots
@@ -109,19 +197,261 @@ impl GameState {
             PlayerType::Player2 => &self.p2_ships
         }
     }
-    pub fn ships_mut(&mut self, player: &PlayerType) -> &mut Vec<Ship> {
+    pub(crate) fn ships_mut(&mut self, player: &PlayerType) -> &mut Vec<Ship> {
         match player {
             PlayerType::Player1 => &mut self.p1_ships,
             PlayerType::Player2 => &mut self.p2_ships
         }
     }
     pub fn last_message(&self) -> Option<&String> {
-        return self.messages.last();
+        self.messages.last()
+    }
+    pub fn messages(&self) -> &Vec<String> {
+        &self.messages
+    }
+    /// Everything that has happened in the game so far, oldest first.
+    pub fn events(&self) -> &[GameEvent] {
+        &self.events
+    }
+    pub fn result(&self) -> GameResult {
+        self.result
+    }
+    pub fn turn(&self) -> &PlayerTurn {
+        &self.turn
+    }
+    pub fn config(&self) -> &GameConfig {
+        &self.config
+    }
+    pub fn width(&self) -> u32 {
+        self.config.width
+    }
+    pub fn height(&self) -> u32 {
+        self.config.height
+    }
+    pub fn in_bounds(&self, location: &Location) -> bool {
+        location.row < self.config.height && location.col < self.config.width
+    }
+
+    /// The player's own board, indexed `[row][col]`: their ships and every shot the opponent has fired at them.
+    pub fn as_own_board(&self, player: &PlayerType) -> Vec<Vec<OwnBoardCell>> {
+        let mut board = vec![vec![OwnBoardCell::Empty; self.width() as usize]; self.height() as usize];
+        for location in self.ships(player).iter().flat_map(|ship| ship.locations.iter()) {
+            board[location.row as usize][location.col as usize] = OwnBoardCell::Ship;
+        }
+        for shot in self.shots(&PlayerType::other(player)) {
+            let cell = &mut board[shot.location.row as usize][shot.location.col as usize];
+            *cell = if shot.hit { OwnBoardCell::ShipHit } else { OwnBoardCell::Miss };
+        }
+        board
+    }
+
+    /// The opponent's board as `player` sees it, indexed `[row][col]`: only the results of their own shots.
+    pub fn as_enemy_board(&self, player: &PlayerType) -> Vec<Vec<EnemyBoardCell>> {
+        let mut board = vec![vec![EnemyBoardCell::Empty; self.width() as usize]; self.height() as usize];
+        for shot in self.shots(player) {
+            board[shot.location.row as usize][shot.location.col as usize] = if shot.hit { EnemyBoardCell::Hit } else { EnemyBoardCell::Miss };
+        }
+        for ship in self.ships(&PlayerType::other(player)).iter().filter(|ship| ship.sunk()) {
+            for location in ship.locations.iter() {
+                board[location.row as usize][location.col as usize] = EnemyBoardCell::Sunk;
+            }
+        }
+        board
+    }
+
+    /// Returns true when the player has placed every ship in their fleet during setup.
+    pub fn ready(&self, player: PlayerType) -> bool {
+        ready(self, player)
+    }
+
+    /// Places a ship of the given class, starting at `location` and extending in `direction`.
+    /// The game starts on its own once both players have placed their whole fleet.
+    pub fn place(&self, player: PlayerType, class: ShipType, location: Location, direction: Direction) -> Result<GameState, GameError> {
+        place(self.clone(), player, class, location, direction)
+    }
+
+    /// Places the player's remaining ships at random, see `placer::auto_place`.
+    pub fn auto_place<R: Rng + ?Sized>(&self, player: PlayerType, rng: &mut R) -> Result<GameState, GameError> {
+        placer::auto_place(self, player, rng)
+    }
+
+    /// Takes back the ship of the given class that covers `location`, so it can be placed again.
+    pub fn remove(&self, player: PlayerType, class: ShipType, location: Location) -> Result<GameState, GameError> {
+        remove(self, player, class, location)
+    }
+
+    /// Fires a shot at the opponent's board on behalf of `player`. Under the Salvo rules this
+    /// is a salvo of one shot. The turn passes to the other player unless `keep_turn` in the
+    /// config lets the player fire again.
+    pub fn fire(&self, player: PlayerType, location: &Location) -> Result<GameState, GameError> {
+        match self.config.shots_per_turn {
+            ShotsPerTurn::Single => fire(self.clone(), player, location),
+            _ => fire_salvo(self.clone(), player, &[*location]),
+        }
+    }
+
+    /// Fires all of the player's shots for this turn at once, see `shots_this_turn`.
+    pub fn fire_salvo(&self, player: PlayerType, locations: &[Location]) -> Result<GameState, GameError> {
+        match (self.config.shots_per_turn, locations) {
+            (ShotsPerTurn::Single, [location]) => fire(self.clone(), player, location),
+            (ShotsPerTurn::Single, _) => Err(GameError::WrongSalvoSize { expected: 1, found: locations.len() as u32 }),
+            _ => fire_salvo(self.clone(), player, locations),
+        }
     }
 
-    // pub fn to_string() -> String {
-    //
-    // }
+    /// How many shots the player gets on their turn, never more than the cells they have left
+    /// to fire at.
+    pub fn shots_this_turn(&self, player: PlayerType) -> u32 {
+        shots_this_turn(self, player)
+    }
+
+    /// Applies a single event on top of this game, checking it against the rules like the
+    /// matching action would.
+    pub fn apply(&self, event: &GameEvent) -> Result<GameState, GameError> {
+        let mut game = self.clone();
+        apply(&mut game, event.clone())?;
+        Ok(game)
+    }
+
+    /// Serializes the whole game, one record per line, in the format read by `from_str`.
+    #[allow(clippy::inherent_to_string)]
+    pub fn to_string(&self) -> String {
+        let mut lines: Vec<String> = vec![format!("battleship|{}", FORMAT_VERSION)];
+        for class in self.config.registry.custom_classes() {
+            lines.push(format!("class|{}", class.to_string()));
+        }
+        lines.push(format!("config|{}", self.config.to_string()));
+        let last_loser = match self.last_loser {
+            Some(player) => player.to_string(),
+            None => String::from("None"),
+        };
+        lines.push(format!("state|{}|{}|{}", self.result, self.turn, last_loser));
+        lines.push(format!("p1_ships|{}", ships_to_string(&self.p1_ships)));
+        lines.push(format!("p1_shots|{}", shots_to_string(&self.p1_shots)));
+        lines.push(format!("p2_ships|{}", ships_to_string(&self.p2_ships)));
+        lines.push(format!("p2_shots|{}", shots_to_string(&self.p2_shots)));
+        for message in self.messages.iter() {
+            lines.push(format!("message|{}", escape(message)));
+        }
+        for event in self.events.iter() {
+            lines.push(format!("event|{}", event.to_string()));
+        }
+        lines.join("\n")
+    }
+}
+
+// messages are free text, so keep them on a single line
+fn escape(message: &str) -> String {
+    message.replace('\\', "\\\\").replace('\n', "\\n").replace('\r', "\\r")
+}
+
+fn unescape(message: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = message.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some(other) => unescaped.push(other),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+impl FromStr for GameState {
+    type Err = GameError;
+
+    fn from_str(input: &str) -> Result<Self, GameError> {
+        let mut version: Option<&str> = None;
+        let mut registry = ShipRegistry::standard();
+        let mut config: Option<&str> = None;
+        let mut state: Option<&str> = None;
+        let mut ships: [Option<&str>; 2] = [None, None];
+        let mut shots: [Option<&str>; 2] = [None, None];
+        let mut messages: Vec<String> = Vec::new();
+        let mut events: Vec<&str> = Vec::new();
+
+        for line in input.lines().filter(|line| !line.is_empty()) {
+            let (key, value) = match line.split_once('|') {
+                Some(parts) => parts,
+                None => return Err(GameError::parse(line, String::from("Expected a | after the record name"))),
+            };
+            match key {
+                "battleship" => version = Some(value),
+                "class" => { registry.register(ShipClass::from_str(value)?)?; }
+                "config" => config = Some(value),
+                "state" => state = Some(value),
+                "p1_ships" => ships[0] = Some(value),
+                "p1_shots" => shots[0] = Some(value),
+                "p2_ships" => ships[1] = Some(value),
+                "p2_shots" => shots[1] = Some(value),
+                "message" => messages.push(unescape(value)),
+                "event" => events.push(value),
+                _ => return Err(GameError::parse(line, format!("Unknown record {}", key))),
+            }
+        }
+
+        let missing = |record: &str| GameError::parse(input, format!("Missing {} record", record));
+        if version != Some(FORMAT_VERSION.to_string().as_str()) && version != Some("1") {
+            return Err(GameError::parse(input, format!("Expected battleship|{} header", FORMAT_VERSION)));
+        }
+
+        let config = GameConfig::parse(config.ok_or_else(|| missing("config"))?, &registry)?;
+
+        let state = state.ok_or_else(|| missing("state"))?;
+        let parts = state.split('|').collect::<Vec<&str>>();
+        if parts.len() != 3 {
+            return Err(GameError::parse(state, format!("Expected state to contain 3 | separated values, found {}", parts.len())));
+        }
+        let last_loser = match parts[2] {
+            "None" => None,
+            player => Some(PlayerType::from_str(player)?),
+        };
+
+        let game = GameState {
+            p1_ships: ships_from_str(ships[0].ok_or_else(|| missing("p1_ships"))?, &config.registry)?,
+            p1_shots: shots_from_str(shots[0].ok_or_else(|| missing("p1_shots"))?)?,
+            p2_ships: ships_from_str(ships[1].ok_or_else(|| missing("p2_ships"))?, &config.registry)?,
+            p2_shots: shots_from_str(shots[1].ok_or_else(|| missing("p2_shots"))?)?,
+            result: GameResult::from_str(parts[0])?,
+            turn: PlayerTurn::from_str(parts[1])?,
+            messages,
+            events: events.into_iter().map(|event| GameEvent::parse(event, &config.registry)).collect::<Result<_, _>>()?,
+            config,
+            last_loser,
+        };
+        check_decoded(&game)?;
+        Ok(game)
+    }
+}
+
+// a saved game is only trusted as far as it could have been played: every location has to be
+// on the grid, and when there is an event log, replaying it has to give the same game
+fn check_decoded(game: &GameState) -> Result<(), GameError> {
+    for player in [PlayerType::Player1, PlayerType::Player2] {
+        let ship_cells = game.ships(&player).iter().flat_map(|ship| ship.locations.iter());
+        let shot_cells = game.shots(&player).iter().map(|shot| &shot.location);
+        if let Some(location) = ship_cells.chain(shot_cells).find(|location| location.row >= game.height() || location.col >= game.width()) {
+            return Err(GameError::parse(&location.to_string(), format!("{} is off the {}x{} grid", location, game.width(), game.height())));
+        }
+    }
+    if game.events.is_empty() {
+        return Ok(());
+    }
+    let replayed = GameState::from_events(&game.events)?;
+    let same = replayed.p1_ships == game.p1_ships && replayed.p1_shots == game.p1_shots
+        && replayed.p2_ships == game.p2_ships && replayed.p2_shots == game.p2_shots
+        && replayed.result == game.result && replayed.turn == game.turn
+        && replayed.config == game.config && replayed.last_loser == game.last_loser;
+    if !same {
+        return Err(GameError::parse(&format!("{} - {}", game.result, game.turn), String::from("The saved game does not match its event log")));
+    }
+    Ok(())
 }
 
 // check if players have placed all ships
@@ -129,201 +459,347 @@ fn ready(game: &GameState, player: PlayerType) -> bool {
     if game.result != GameResult::InSetup {
         return false
     }
-    return match player {
-        PlayerType::Player1 => {
-            for ship in game.p1_ships.iter() {
-                if ship.locations.len() == 0 {
-                    return false;
-                }
-            }
-            true
-        }
-        PlayerType::Player2 => {
-            for ship in game.p2_ships.iter() {
-                if ship.locations.len() == 0 {
-                    return false;
-                }
-            }
-            true
+    game.ships(&player).iter().all(|ship| !ship.locations.is_empty())
+}
+
+// who takes the first shot once both fleets are in place
+fn first_shooter(game: &GameState) -> PlayerType {
+    match game.config.first_shooter {
+        FirstShooter::Player1 => PlayerType::Player1,
+        FirstShooter::Random => {
+            if rand::random::<bool>() { PlayerType::Player1 } else { PlayerType::Player2 }
         }
+        FirstShooter::LoserOfLastGame => game.last_loser.unwrap_or(PlayerType::Player1),
     }
 }
 
-fn expand(location: Location, class: ShipType, direction: Direction) -> Result<Vec<Location>, String> {
-    let size = class.size();
-    match direction {
-        Direction::Up => {
-            if location.row - size > 0 {
-                let mut v: Vec<Location> = Vec::new();
-                for row in (location.row - size)..location.row {
-                    v.push(Location { row, col: location.col });
-                }
-                return Ok(v);
-            }
+// the locations a ship covers when placed at `location` facing `direction`
+fn expand(game: &GameState, location: Location, class: &ShipType, direction: Direction) -> Result<Vec<Location>, GameError> {
+    let mut locations: Vec<Location> = Vec::new();
+    for (row_offset, col_offset) in class.footprint(direction) {
+        let row = location.row as i64 + row_offset;
+        let col = location.col as i64 + col_offset;
+        if row < 0 || col < 0 || row >= game.height() as i64 || col >= game.width() as i64 {
+            return Err(GameError::NoRoom { class: class.clone(), location, direction });
         }
-        Direction::Down => {
-            if location.row + size < GRID_HEIGHT {
-                let mut v: Vec<Location> = Vec::new();
-                for row in location.row..(location.row+size) {
-                    v.push(Location { row, col: location.col });
-                }
-                return Ok(v);
+        locations.push(Location { row: row as u32, col: col as u32 });
+    }
+    Ok(locations)
+}
+
+fn ship_at(game: &GameState, player: &PlayerType, location: &Location) -> bool {
+    for ship in game.ships(player) {
+        for ship_location in ship.locations.iter() {
+            if ship_location.eq(location) {
+                return true;
             }
         }
-        Direction::Left => {
-            if location.col - size > 0 {
-                let mut v: Vec<Location> = Vec::new();
-                for col in (location.col - size)..location.col {
-                    v.push(Location { row: location.row, col });
-                }
-                return Ok(v);
+    }
+
+    false
+}
+
+// the first of the player's ships the cells would touch under the no-touch rule
+fn touching(game: &GameState, player: &PlayerType, cells: &[Location]) -> Option<ShipType> {
+    let neighbours = game.config.no_touch.neighbours();
+    for cell in cells {
+        for (row, col) in neighbours {
+            let (row, col) = (cell.row as i64 + row, cell.col as i64 + col);
+            if row < 0 || col < 0 {
+                continue;
             }
-        }
-        Direction::Right => {
-            if location.col + size < GRID_WIDTH {
-                let mut v: Vec<Location> = Vec::new();
-                for col in location.col..(location.col+size) {
-                    v.push(Location { row: location.row, col });
-                }
-                return Ok(v);
+            let neighbour = Location { row: row as u32, col: col as u32 };
+            if let Some(ship) = game.ships(player).iter().find(|ship| ship.locations.contains(&neighbour)) {
+                return Some(ship.class.clone());
             }
         }
     }
-    Err(format!("Not enough room to place a {} at {} {}", class, location, direction))
+    None
 }
 
-fn ship_at(game: &GameState, player: &PlayerType, location: &Location) -> bool {
-    for ship in game.ships(&player) {
-        for ship_location in ship.locations.iter() {
-            if ship_location.eq(&location) {
-                return true;
+fn place(game_state: GameState, player: PlayerType, class: ShipType, location: Location, direction: Direction) -> Result<GameState, GameError> {
+    let mut game = game_state;
+    apply(&mut game, GameEvent::ShipPlaced { player, class, location, direction })?;
+    // once both fleets are in place the game starts on its own
+    if ready(&game, PlayerType::Player1) && ready(&game, PlayerType::Player2) {
+        let first = first_shooter(&game);
+        apply(&mut game, GameEvent::GameStarted { first })?;
+    }
+    Ok(game)
+}
+
+fn remove(game_state: &GameState, player: PlayerType, class: ShipType, location: Location) -> Result<GameState, GameError> {
+    let mut game = game_state.clone();
+    apply(&mut game, GameEvent::ShipRemoved { player, class, location })?;
+    Ok(game)
+}
+
+fn fire(game_state: GameState, player: PlayerType, location: &Location) -> Result<GameState, GameError> {
+    let mut game = game_state;
+    let target = game.ships(&PlayerType::other(&player)).iter().find(|ship| ship.locations.contains(location));
+    let hit = target.is_some();
+    let sunk = target.filter(|ship| ship.hits + 1 == ship.class.size()).map(|ship| ship.class.clone());
+    apply(&mut game, GameEvent::ShotFired { player, location: *location, hit, sunk })?;
+    if game.ships(&PlayerType::other(&player)).iter().all(|ship| ship.sunk()) {
+        apply(&mut game, GameEvent::GameOver { winner: player })?;
+    }
+    Ok(game)
+}
+
+fn fire_salvo(game_state: GameState, player: PlayerType, locations: &[Location]) -> Result<GameState, GameError> {
+    let mut game = game_state;
+    let mut ships = game.ships(&PlayerType::other(&player)).clone();
+    let mut shots = Vec::new();
+    let mut sunk = Vec::new();
+    // resolved against a copy of the enemy fleet so a ship hit twice in one salvo is only sunk once
+    for location in locations {
+        let target = ships.iter_mut().find(|ship| ship.locations.contains(location));
+        shots.push(Shot { location: *location, hit: target.is_some() });
+        if let Some(ship) = target {
+            ship.hits += 1;
+            if ship.sunk() {
+                sunk.push(ship.class.clone());
             }
         }
     }
+    apply(&mut game, GameEvent::SalvoFired { player, shots, sunk })?;
+    if game.ships(&PlayerType::other(&player)).iter().all(|ship| ship.sunk()) {
+        apply(&mut game, GameEvent::GameOver { winner: player })?;
+    }
+    Ok(game)
+}
 
-    return false;
+fn shots_this_turn(game: &GameState, player: PlayerType) -> u32 {
+    let shots = match game.config.shots_per_turn {
+        ShotsPerTurn::Single => 1,
+        ShotsPerTurn::Fixed(shots) => shots,
+        ShotsPerTurn::ShipsAfloat => game.ships(&player).iter().filter(|ship| !ship.sunk()).count() as u32,
+    };
+    // u64 so a grid that skipped validation can't overflow
+    let cells_left = (game.width() as u64 * game.height() as u64).saturating_sub(game.shots(&player).len() as u64);
+    (shots as u64).min(cells_left) as u32
 }
 
-fn place(game_state: GameState, player: PlayerType, class: ShipType, location: Location, direction: Direction) -> Result<GameState, String> {
-    if !game_state.result.eq(&GameResult::InSetup) {
-        return Err(format!("Cannot place ships after the game has started."));
+// checks the event against the rules, then updates the game and appends the event to its log
+fn apply(game: &mut GameState, event: GameEvent) -> Result<(), GameError> {
+    match &event {
+        GameEvent::Created { config, last_loser } => {
+            config.validate()?;
+            *game = GameState::setup(config.clone(), *last_loser);
+            // setup has already logged the event
+            return Ok(());
+        }
+        GameEvent::ShipPlaced { player, class, location, direction } =>
+            apply_placed(game, *player, class, *location, *direction)?,
+        GameEvent::ShipRemoved { player, class, location } =>
+            apply_removed(game, *player, class, *location)?,
+        GameEvent::GameStarted { first } => {
+            if !ready(game, PlayerType::Player1) || !ready(game, PlayerType::Player2) {
+                return Err(GameError::InvalidEvent { event: event.to_string(), reason: String::from("Both fleets have to be placed before the game starts") });
+            }
+            let expected = match game.config.first_shooter {
+                FirstShooter::Random => *first,
+                _ => first_shooter(game),
+            };
+            if *first != expected {
+                return Err(GameError::InvalidEvent { event: event.to_string(), reason: format!("{} has to fire first", expected) });
+            }
+            game.result = GameResult::InProgress;
+            game.turn = PlayerTurn::from(first);
+            game.messages.push(format!("All ships have been placed. {} fires first!", first));
+        }
+        GameEvent::ShotFired { player, location, hit, sunk } => {
+            if game.config.shots_per_turn != ShotsPerTurn::Single {
+                return Err(GameError::InvalidEvent { event: event.to_string(), reason: String::from("Shots are fired in salvos in this game") });
+            }
+            let outcome = apply_fired(game, *player, location)?;
+            if outcome != (*hit, sunk.clone()) {
+                return Err(GameError::InvalidEvent { event: event.to_string(), reason: format!("The shot {}", describe_shot(&outcome)) });
+            }
+        }
+        GameEvent::SalvoFired { player, shots, sunk } => {
+            if game.config.shots_per_turn == ShotsPerTurn::Single {
+                return Err(GameError::InvalidEvent { event: event.to_string(), reason: String::from("Shots are fired one at a time in this game") });
+            }
+            let (hits, sunk_by_salvo) = apply_salvo(game, *player, shots)?;
+            if shots.iter().map(|shot| shot.hit).ne(hits) || *sunk != sunk_by_salvo {
+                return Err(GameError::InvalidEvent { event: event.to_string(), reason: String::from("The salvo's hits or sunk ships are not what happened") });
+            }
+        }
+        GameEvent::GameOver { winner } => {
+            if game.result != GameResult::InProgress || game.ships(&PlayerType::other(winner)).iter().any(|ship| !ship.sunk()) {
+                return Err(GameError::InvalidEvent { event: event.to_string(), reason: format!("{} still has ships afloat", PlayerType::other(winner)) });
+            }
+            game.result = GameResult::player_win(winner);
+            game.messages.push(format!("Game over. {} wins!", winner));
+            game.turn = PlayerTurn::Neither;
+        }
     }
+    game.events.push(event);
+    Ok(())
+}
 
-    let mut game = game_state.clone();
+fn apply_placed(game: &mut GameState, player: PlayerType, class: &ShipType, location: Location, direction: Direction) -> Result<(), GameError> {
+    if !game.result.eq(&GameResult::InSetup) {
+        return Err(GameError::WrongPhase { expected: GameResult::InSetup, actual: game.result });
+    }
 
     // validate placement
-    let mut expanded_locations = expand(location, class, direction)?;
+    let mut expanded_locations = expand(game, location, class, direction)?;
     for loc in expanded_locations.iter() {
-        if ship_at(&game_state, &player, &loc) {
-            return Err(format!("Cannot place a ship at {} {}, as it would overlap another ship.", location, direction));
+        if ship_at(game, &player, loc) {
+            return Err(GameError::Overlap { location, direction });
         }
     }
+    if let Some(touching) = touching(game, &player, &expanded_locations) {
+        return Err(GameError::Touching { location, direction, touching });
+    }
 
     // attempt to place
-    for mut ship in game.ships_mut(&player) {
-        if ship.class == class && ship.locations.len() == 0 {
+    for ship in game.ships_mut(&player) {
+        if ship.class == *class && ship.locations.is_empty() {
             ship.locations.append(&mut expanded_locations);
-            return Ok(game);
+            return Ok(());
         }
     }
-    Err(format!("There are no ships of class {} left to place", class))
+    Err(GameError::NoShipsOfClassLeft { class: class.clone() })
 }
 
-fn remove(game_state: &GameState, player: PlayerType, class: ShipType, location: Location) -> Result<GameState, String> {
-    let mut game = game_state.clone();
-    for mut ship in game.ships_mut(&player) {
-        if ship.class == class && ship.locations.contains( &location) {
+fn apply_removed(game: &mut GameState, player: PlayerType, class: &ShipType, location: Location) -> Result<(), GameError> {
+    if !game.result.eq(&GameResult::InSetup) {
+        return Err(GameError::WrongPhase { expected: GameResult::InSetup, actual: game.result });
+    }
+    for ship in game.ships_mut(&player) {
+        if ship.class == *class && ship.locations.contains(&location) {
             ship.locations.clear();
-            return Ok(game);
+            return Ok(());
         }
     }
 
-    return Err(format!("Could not find a {} at {}", class, location));
+    Err(GameError::ShipNotFound { class: class.clone(), location })
+}
+
+// fires the shot, returning whether it hit and the class of the ship it sank
+fn apply_fired(game: &mut GameState, player: PlayerType, location: &Location) -> Result<(bool, Option<ShipType>), GameError> {
+    check_turn(game, player)?;
+    let (hit, sunk) = record_shot(game, player, location)?;
+    end_turn(game, player, hit, sunk.is_some());
+    Ok((hit, sunk))
+}
+
+fn describe_shot(outcome: &(bool, Option<ShipType>)) -> String {
+    match outcome {
+        (_, Some(class)) => format!("sank the {}", class),
+        (true, None) => String::from("hit"),
+        (false, None) => String::from("missed"),
+    }
+}
+
+// fires the salvo, returning whether each shot hit and the classes of the ships it sank
+fn apply_salvo(game: &mut GameState, player: PlayerType, shots: &[Shot]) -> Result<(Vec<bool>, Vec<ShipType>), GameError> {
+    check_turn(game, player)?;
+    let expected = shots_this_turn(game, player);
+    if shots.len() as u32 != expected {
+        return Err(GameError::WrongSalvoSize { expected, found: shots.len() as u32 });
+    }
+    // each shot is recorded before the next is checked, so firing twice at a cell in one salvo
+    // is caught like any other repeated shot
+    let (mut hits, mut sunk) = (Vec::new(), Vec::new());
+    for shot in shots {
+        let (hit, sunk_class) = record_shot(game, player, &shot.location)?;
+        hits.push(hit);
+        sunk.extend(sunk_class);
+    }
+    end_turn(game, player, hits.contains(&true), !sunk.is_empty());
+    Ok((hits, sunk))
+}
+
+// passes the turn to the other player, unless the rules let the player fire again
+fn end_turn(game: &mut GameState, player: PlayerType, hit: bool, sunk: bool) {
+    let again = match game.config.keep_turn {
+        KeepTurn::Never => false,
+        KeepTurn::OnHit => hit,
+        KeepTurn::OnSink => sunk,
+    };
+    if !again {
+        game.turn = PlayerTurn::from(&PlayerType::other(&player));
+    } else if game.ships(&PlayerType::other(&player)).iter().any(|ship| !ship.sunk()) {
+        // no need to announce it after the last ship goes down, the game is over
+        game.messages.push(format!("{} fires again!", player));
+    }
 }
 
-fn fire(game_state: GameState, player: PlayerType, location: &Location) -> Result<GameState, String> {
-    if !game_state.result.eq(&GameResult::InProgress) {
-        return Err(format!("Cannot fire when game is not in progress"));
+fn check_turn(game: &GameState, player: PlayerType) -> Result<(), GameError> {
+    if !game.result.eq(&GameResult::InProgress) {
+        return Err(GameError::WrongPhase { expected: GameResult::InProgress, actual: game.result });
     }
-    if !game_state.turn.eq(&PlayerTurn::from(&player)) {
-        return Err(format!("{} cannot fire, it is not their turn.", player))
+    if !game.turn.eq(&PlayerTurn::from(&player)) {
+        return Err(GameError::NotYourTurn { player })
     }
-    // location fields use usize, so don't have to check for < 0
-    if &location.row >= &GRID_HEIGHT || &location.col >= &GRID_WIDTH {
-        return Err(format!("Invalid fire coordinates {}, must be between {} and {}.", &location, Location{row:0, col:0}, Location{row: GRID_HEIGHT-1, col: GRID_WIDTH-1}));
+    Ok(())
+}
+
+// records one shot and its message, returning whether it hit and the class of the ship it sank
+fn record_shot(game: &mut GameState, player: PlayerType, location: &Location) -> Result<(bool, Option<ShipType>), GameError> {
+    // location fields are unsigned, so don't have to check for < 0
+    if !game.in_bounds(location) {
+        return Err(GameError::OutOfBounds { location: *location, width: game.width(), height: game.height() });
     }
 
-    //for shots in game_state.p1_shots
-    for shot in game_state.shots(&player) {
+    for shot in game.shots(&player) {
         if shot.location.eq(location) {
-            return Err(format!("Cannot fire on {}, you have already fired there!", &location));
+            return Err(GameError::AlreadyFired { location: *location });
         }
     }
-    let mut next_state = game_state.clone();
     let mut hit = false;
     let mut sunk = false;
     let mut class = ShipType::Battleship;
 
-    'outer: for mut ship in next_state.ships_mut(&PlayerType::other(&player)) {
+    'outer: for ship in game.ships_mut(&PlayerType::other(&player)) {
         for ship_location in &ship.locations {
             if ship_location.eq(location) {
                 hit = true;
                 ship.hits += 1;
                 sunk = ship.sunk();
-                class = ship.class;
+                class = ship.class.clone();
                 break 'outer;
             }
         }
     }
 
-    let mut shots = next_state.shots_mut(&player);
-    shots.push(Shot{location: location.clone(), hit });
-
+    let shots = game.shots_mut(&player);
+    shots.push(Shot{location: *location, hit });
 
     if hit & sunk {
-        next_state.messages.push(format!("{} sunk {}'s {}!", player, PlayerType::other(&player), class));
+        game.messages.push(format!("{} sunk {}'s {}!", player, PlayerType::other(&player), class));
     } else if hit {
-        next_state.messages.push(format!("{} fires at {} and hits {}'s ship!", player, location, &PlayerType::other(&player)));
+        game.messages.push(format!("{} fires at {} and hits {}'s ship!", player, location, &PlayerType::other(&player)));
     } else {
-        next_state.messages.push(format!("{} fires at {} and misses!", player, location));
+        game.messages.push(format!("{} fires at {} and misses!", player, location));
     }
-
-    // check for win condition
-    let mut unsunk_ships_remain = false;
-    for ship in next_state.ships(&PlayerType::other(&player)) {
-        if ship.sunk() == false {
-            unsunk_ships_remain = true;
-            break;
-        }
-    }
-
-    if unsunk_ships_remain == false {
-        next_state.result = GameResult::player_win(&player);
-        next_state.messages.push(format!("Game over. {} wins!", player));
-        next_state.turn = PlayerTurn::Neither;
-    } else {
-        next_state.turn = PlayerTurn::from(&PlayerType::other(&player));
-    }
-
-    return Ok(next_state);
+    Ok((hit, if sunk { Some(class) } else { None }))
 }
 
-
-// fn asOwnBoard(player: PlayerType) -> [[OwnBoardCell; 10]; 10] {}
-// fn asEnemyBoard(player: PlayerType) -> [[OwnBoardCell; 10]; 10] {}
 #[cfg(test)]
 mod tests {
-    use crate::game::{Direction, fire, GameResult, GameState, GRID_HEIGHT, GRID_WIDTH, place};
+    use std::str::FromStr;
+    use crate::config::{FirstShooter, GameConfig, KeepTurn, NoTouch, ShotsPerTurn};
+    use crate::error::GameError;
+    use crate::event::GameEvent;
+    use crate::fleet::FleetSpec;
+    use crate::game::{Direction, EnemyBoardCell, fire, GameResult, GameState, OwnBoardCell, place, remove};
     use crate::location::Location;
     use crate::player::{PlayerTurn, PlayerType};
-    use crate::ship::{Ship, ShipType};
+    use crate::registry::ShipRegistry;
+    use crate::ship::{Ship, ShipClass, ShipType};
+    use crate::shot::Shot;
 
     #[test]
     fn can_place_ships() {
         let state = GameState::new();
         let player = PlayerType::Player1;
         let class = ShipType::Submarine;
-        let state_2 = place(state, player, class, Location{row:0, col:0}, Direction::Down);
+        let state_2 = place(state, player, class.clone(), Location{row:0, col:0}, Direction::Down);
         assert!(state_2.is_ok());
         let state_3 = place(state_2.unwrap(), player, class, Location{row:0, col:1}, Direction::Down);
         assert!(state_3.is_ok());
@@ -334,13 +810,13 @@ mod tests {
         let state = GameState::new();
         let player = PlayerType::Player1;
         let class = ShipType::Submarine;
-        let state_2 = place(state, player, class, Location{row:0, col:0}, Direction::Down);
+        let state_2 = place(state, player, class.clone(), Location{row:0, col:0}, Direction::Down);
         assert!(state_2.is_ok());
-        let state_3 = place(state_2.unwrap(), player, class, Location{row:0, col:1}, Direction::Down);
+        let state_3 = place(state_2.unwrap(), player, class.clone(), Location{row:0, col:1}, Direction::Down);
         assert!(state_3.is_ok());
-        let state_4 = place(state_3.unwrap(), player, class, Location{row:0, col:2}, Direction::Down);
+        let state_4 = place(state_3.unwrap(), player, class.clone(), Location{row:0, col:2}, Direction::Down);
         assert!(state_4.is_err());
-        assert!(state_4.err().unwrap().contains("There are no ships of class"));
+        assert_eq!(state_4.err().unwrap(), GameError::NoShipsOfClassLeft { class });
     }
 
     #[test]
@@ -348,11 +824,93 @@ mod tests {
         let state = GameState::new();
         let player = PlayerType::Player1;
         let class = ShipType::Submarine;
-        let state_2 = place(state, player, class, Location{row:0, col:0}, Direction::Down);
+        let state_2 = place(state, player, class.clone(), Location{row:0, col:0}, Direction::Down);
         assert!(state_2.is_ok());
         let state_3 = place(state_2.unwrap(), player, class, Location{row:0, col:0}, Direction::Down);
         assert!(state_3.is_err());
-        assert!(state_3.err().unwrap().contains("as it would overlap another ship"));
+        assert_eq!(state_3.err().unwrap(), GameError::Overlap { location: Location{row:0, col:0}, direction: Direction::Down });
+    }
+
+    #[test]
+    fn no_touch_rule_keeps_ships_apart() {
+        let end_on = (Location{row:0, col:2}, Direction::Right);
+        let corner = (Location{row:1, col:2}, Direction::Right);
+        let cases = [(NoTouch::Orthogonal, true, false), (NoTouch::Diagonal, false, true), (NoTouch::All, true, true), (NoTouch::Off, false, false)];
+        for (no_touch, end_on_touches, corner_touches) in cases {
+            let state = GameState::with_config(GameConfig { no_touch, ..GameConfig::default() }).unwrap();
+            let state = place(state, PlayerType::Player1, ShipType::Destroyer, Location{row:0, col:0}, Direction::Right).unwrap();
+            for ((location, direction), touches) in [(end_on, end_on_touches), (corner, corner_touches)] {
+                let result = place(state.clone(), PlayerType::Player1, ShipType::Submarine, location, direction);
+                if touches {
+                    assert_eq!(result.err(), Some(GameError::Touching { location, direction, touching: ShipType::Destroyer }), "{}", no_touch);
+                } else {
+                    assert!(result.is_ok(), "{}", no_touch);
+                }
+            }
+        }
+        // only your own ships count
+        let state = GameState::with_config(GameConfig { no_touch: NoTouch::All, ..GameConfig::default() }).unwrap();
+        let state = place(state, PlayerType::Player1, ShipType::Destroyer, Location{row:0, col:0}, Direction::Right).unwrap();
+        assert!(place(state, PlayerType::Player2, ShipType::Submarine, end_on.0, end_on.1).is_ok());
+    }
+
+    #[test]
+    fn game_uses_configured_fleet() {
+        let config = GameConfig { fleet: FleetSpec::classic(), ..GameConfig::default() };
+        let state = GameState::with_config(config).unwrap();
+        assert_eq!(state.ships(&PlayerType::Player1).len(), 5);
+        assert_eq!(state.ships(&PlayerType::Player2).len(), 5);
+        let state = place_fleet(state, PlayerType::Player1);
+        let state = place_fleet(state, PlayerType::Player2);
+        assert!(state.result.eq(&GameResult::InProgress));
+    }
+
+    #[test]
+    fn custom_ship_classes_can_be_placed_and_sunk() {
+        let mut registry = ShipRegistry::standard();
+        let corner = registry.register(ShipClass::shaped("Corner", vec![Location{row:0, col:0}, Location{row:0, col:1}, Location{row:1, col:0}])).unwrap();
+        let config = GameConfig { registry, fleet: FleetSpec::new(vec![(corner.clone(), 1)]), ..GameConfig::default() };
+        let state = GameState::with_config(config).unwrap();
+
+        // facing Down the shape turns clockwise
+        let state = place(state, PlayerType::Player1, corner.clone(), Location{row:5, col:5}, Direction::Down).unwrap();
+        let ship = &state.ships(&PlayerType::Player1)[0];
+        assert_eq!(ship.locations, vec![Location{row:5, col:5}, Location{row:6, col:5}, Location{row:5, col:4}]);
+
+        let mut state = place(state, PlayerType::Player2, corner, Location{row:0, col:0}, Direction::Right).unwrap();
+        assert!(state.result.eq(&GameResult::InProgress));
+        for location in [Location{row:0, col:0}, Location{row:0, col:1}, Location{row:1, col:0}] {
+            state.turn = PlayerTurn::Player1;
+            state = fire(state, PlayerType::Player1, &location).unwrap();
+        }
+        assert!(state.messages.iter().any(|message| message == "Player 1 sunk Player 2's Corner!"));
+        assert!(state.result.eq(&GameResult::Player1Win));
+    }
+
+    #[test]
+    fn placement_respects_grid_size() {
+        let state = GameState::with_config(GameConfig::with_grid(8, 8)).unwrap();
+        let player = PlayerType::Player1;
+        let state_2 = place(state.clone(), player, ShipType::Carrier, Location{row:7, col:3}, Direction::Right);
+        assert!(state_2.is_ok());
+        let state_3 = place(state.clone(), player, ShipType::Carrier, Location{row:7, col:4}, Direction::Right);
+        assert_eq!(state_3.err().unwrap(), GameError::NoRoom { class: ShipType::Carrier, location: Location{row:7, col:4}, direction: Direction::Right });
+        let state_4 = place(state, player, ShipType::Carrier, Location{row:8, col:0}, Direction::Right);
+        assert!(matches!(state_4.err().unwrap(), GameError::NoRoom { .. }));
+    }
+
+    #[test]
+    fn placement_extends_from_the_given_location() {
+        let state = GameState::new();
+        let player = PlayerType::Player1;
+        let state_2 = place(state.clone(), player, ShipType::Submarine, Location{row:0, col:0}, Direction::Up);
+        assert!(matches!(state_2.err().unwrap(), GameError::NoRoom { .. }));
+        let state_3 = place(state.clone(), player, ShipType::Submarine, Location{row:0, col:0}, Direction::Left);
+        assert!(matches!(state_3.err().unwrap(), GameError::NoRoom { .. }));
+
+        let state_4 = place(state, player, ShipType::Submarine, Location{row:5, col:5}, Direction::Up).unwrap();
+        let submarine = state_4.ships(&player).iter().find(|ship| !ship.locations.is_empty()).unwrap();
+        assert_eq!(submarine.locations, vec![Location{row:5, col:5}, Location{row:4, col:5}]);
     }
 
     #[test]
@@ -364,7 +922,7 @@ mod tests {
 
         let state_2 = place(state, player, class, Location{row:0, col:0}, Direction::Down);
         assert!(state_2.is_err());
-        assert!(state_2.err().unwrap().contains("after the game has started"));
+        assert_eq!(state_2.err().unwrap(), GameError::WrongPhase { expected: GameResult::InSetup, actual: GameResult::InProgress });
     }
 
     #[test]
@@ -374,7 +932,7 @@ mod tests {
 
         let state_2 = fire(state, player, &Location{row:0, col:0});
         assert!(state_2.is_err());
-        assert!(state_2.err().unwrap().contains("Cannot fire when game is not in progress"));
+        assert_eq!(state_2.err().unwrap(), GameError::WrongPhase { expected: GameResult::InProgress, actual: GameResult::InSetup });
     }
 
     #[test]
@@ -386,7 +944,7 @@ mod tests {
 
         let state_2 = fire(state, player, &Location{row:0, col:0});
         assert!(state_2.is_err());
-        assert!(state_2.err().unwrap().contains("cannot fire, it is not their turn."));
+        assert_eq!(state_2.err().unwrap(), GameError::NotYourTurn { player });
     }
 
     #[test]
@@ -396,13 +954,25 @@ mod tests {
         state.turn = PlayerTurn::Player1;
         let player = PlayerType::Player1;
 
-        let state_2 = fire(state.clone(), player, &Location{row:GRID_HEIGHT, col:0});
+        let state_2 = fire(state.clone(), player, &Location{row:state.height(), col:0});
         assert!(state_2.is_err());
-        assert!(state_2.err().unwrap().contains("Invalid fire coordinates"));
+        assert!(matches!(state_2.err().unwrap(), GameError::OutOfBounds { .. }));
 
-        let state_3 = fire(state, player, &Location{row:0, col:GRID_WIDTH});
+        let state_3 = fire(state.clone(), player, &Location{row:0, col:state.width()});
         assert!(state_3.is_err());
-        assert!(state_3.err().unwrap().contains("Invalid fire coordinates"));
+        assert!(matches!(state_3.err().unwrap(), GameError::OutOfBounds { .. }));
+    }
+
+    #[test]
+    fn fire_respects_grid_size() {
+        let mut state = GameState::with_config(GameConfig::with_grid(15, 15)).unwrap();
+        state.result = GameResult::InProgress;
+        state.turn = PlayerTurn::Player1;
+        let player = PlayerType::Player1;
+
+        assert!(fire(state.clone(), player, &Location{row:14, col:14}).is_ok());
+        let state_2 = fire(state, player, &Location{row:15, col:0});
+        assert_eq!(state_2.err().unwrap(), GameError::OutOfBounds { location: Location{row:15, col:0}, width: 15, height: 15 });
     }
 
     #[test]
@@ -416,7 +986,7 @@ mod tests {
         state_2.turn = PlayerTurn::Player1;
         let state_3 = fire(state_2, player, &Location{row:0, col:0});
         assert!(state_3.is_err());
-        assert!(state_3.err().unwrap().contains("you have already fired there"));
+        assert_eq!(state_3.err().unwrap(), GameError::AlreadyFired { location: Location{row:0, col:0} });
     }
 
     #[test]
@@ -454,7 +1024,7 @@ mod tests {
         let player = PlayerType::Player1;
         let mut state_3 = fire(state_2, player, &Location{row:0, col:0}).unwrap();
         state_3.turn = PlayerTurn::Player1;
-        let mut state_4 = fire(state_3, player, &Location{row:1, col:0}).unwrap();
+        let state_4 = fire(state_3, player, &Location{row:1, col:0}).unwrap();
         let message = state_4.last_message().unwrap();
         assert!(message.contains(&String::from("sunk")));
         assert!(state_4.turn.eq(&PlayerTurn::Player2));
@@ -480,6 +1050,9 @@ mod tests {
             result: GameResult::InProgress,
             turn: PlayerTurn::Player1,
             messages: vec![String::from("It's Player 1's turn.")],
+            config: GameConfig::default(),
+            last_loser: None,
+            events: Vec::new(),
         };
         let player = PlayerType::Player1;
         let mut state_2 = fire(state, player, &Location{row:0,col:0}).unwrap();
@@ -491,4 +1064,346 @@ mod tests {
         assert!(message.contains(&String::from("Game over. Player 1 wins!")));
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn own_and_enemy_boards() {
+        let state = GameState::with_config(GameConfig { fleet: FleetSpec::new(vec![(ShipType::Destroyer, 1), (ShipType::TorpedoBoat, 1)]), ..GameConfig::with_grid(4, 3) }).unwrap();
+        let state = place(state, PlayerType::Player1, ShipType::Destroyer, Location{row:0, col:0}, Direction::Right).unwrap();
+        let state = place(state, PlayerType::Player1, ShipType::TorpedoBoat, Location{row:2, col:3}, Direction::Right).unwrap();
+        let state = place(state, PlayerType::Player2, ShipType::Destroyer, Location{row:1, col:1}, Direction::Down).unwrap();
+        let state = place(state, PlayerType::Player2, ShipType::TorpedoBoat, Location{row:0, col:3}, Direction::Right).unwrap();
+
+        let state = fire(state, PlayerType::Player1, &Location{row:0, col:3}).unwrap();
+        let state = fire(state, PlayerType::Player2, &Location{row:0, col:1}).unwrap();
+        let state = fire(state, PlayerType::Player1, &Location{row:1, col:1}).unwrap();
+        let state = fire(state, PlayerType::Player2, &Location{row:1, col:0}).unwrap();
+        let state = fire(state, PlayerType::Player1, &Location{row:0, col:0}).unwrap();
+
+        use OwnBoardCell as O;
+        assert_eq!(state.as_own_board(&PlayerType::Player1), vec![
+            vec![O::Ship, O::ShipHit, O::Empty, O::Empty],
+            vec![O::Miss, O::Empty, O::Empty, O::Empty],
+            vec![O::Empty, O::Empty, O::Empty, O::Ship],
+        ]);
+        use EnemyBoardCell as E;
+        assert_eq!(state.as_enemy_board(&PlayerType::Player1), vec![
+            vec![E::Miss, E::Empty, E::Empty, E::Sunk],
+            vec![E::Empty, E::Hit, E::Empty, E::Empty],
+            vec![E::Empty, E::Empty, E::Empty, E::Empty],
+        ]);
+        // player 2 never learns where the rest of player 1's fleet is
+        assert_eq!(state.as_enemy_board(&PlayerType::Player2), vec![
+            vec![E::Empty, E::Hit, E::Empty, E::Empty],
+            vec![E::Miss, E::Empty, E::Empty, E::Empty],
+            vec![E::Empty, E::Empty, E::Empty, E::Empty],
+        ]);
+    }
+
+    #[test]
+    fn new_game_to_string() {
+        let state = GameState::new();
+        assert_eq!(state.to_string(), [
+            "battleship|2",
+            "config|Player 1|10x10|Battleship:1;Carrier:2;Submarine:2;Destroyer:2",
+            "state|In Setup|Either Player|None",
+            "p1_ships|Battleship|0|&Carrier|0|&Carrier|0|&Submarine|0|&Submarine|0|&Destroyer|0|&Destroyer|0|",
+            "p1_shots|",
+            "p2_ships|Battleship|0|&Carrier|0|&Carrier|0|&Submarine|0|&Submarine|0|&Destroyer|0|&Destroyer|0|",
+            "p2_shots|",
+            "message|Players, please place your ships to begin the game!",
+            "event|created|None|Player 1|10x10|Battleship:1;Carrier:2;Submarine:2;Destroyer:2",
+        ].join("\n"));
+        assert_eq!(GameState::from_str(&state.to_string()).unwrap(), state);
+    }
+
+    #[test]
+    fn messages_are_escaped() {
+        let mut state = GameState::new();
+        state.messages.push(String::from("two\nlines with a \\ and | pipe"));
+        state.messages.push(String::new());
+        let serialized = state.to_string();
+        assert_eq!(serialized.lines().count(), 11);
+        assert_eq!(GameState::from_str(&serialized).unwrap().messages, state.messages);
+    }
+
+    #[test]
+    fn game_in_progress_to_string_and_back() {
+        let state = place_fleet(GameState::new(), PlayerType::Player1);
+        let state = place_fleet(state, PlayerType::Player2);
+        let state = fire(state, PlayerType::Player1, &Location{row:0, col:0}).unwrap();
+        let state = fire(state, PlayerType::Player2, &Location{row:9, col:9}).unwrap();
+        let decoded = GameState::from_str(&state.to_string()).unwrap();
+        assert_eq!(decoded, state);
+        assert_eq!(decoded.ships(&PlayerType::Player2)[0].hits, 1);
+    }
+
+    #[test]
+    fn tampered_files_are_rejected() {
+        let state = place_fleet(GameState::new(), PlayerType::Player1);
+        let state = place_fleet(state, PlayerType::Player2);
+        let state = fire(state, PlayerType::Player1, &Location{row:0, col:0}).unwrap();
+        let serialized = state.to_string();
+        assert!(serialized.contains("\np1_shots|0,0|true\n"));
+
+        // locations off the grid, with and without an event log to check against
+        let off_grid = serialized.replace("\np1_shots|0,0|", "\np1_shots|50,50|");
+        assert!(matches!(GameState::from_str(&off_grid), Err(GameError::ParseError { .. })));
+        let version_1: Vec<&str> = off_grid.lines().filter(|line| !line.starts_with("event|")).collect();
+        let version_1 = version_1.join("\n").replace("battleship|2", "battleship|1");
+        assert!(matches!(GameState::from_str(&version_1), Err(GameError::ParseError { .. })));
+
+        // a shot moved somewhere else on the grid no longer matches the log
+        let moved = serialized.replace("\np1_shots|0,0|true", "\np1_shots|5,5|true");
+        assert!(matches!(GameState::from_str(&moved), Err(GameError::ParseError { .. })));
+
+        // a game that never started can't be made playable by editing its state
+        let unstarted = GameState::new().to_string().replace("state|In Setup|Either Player", "state|In Progress|Player 1");
+        assert_ne!(unstarted, GameState::new().to_string());
+        assert!(matches!(GameState::from_str(&unstarted), Err(GameError::ParseError { .. })));
+    }
+
+    // places every ship in the player's fleet on its own row, starting at column 0
+    fn place_fleet(state: GameState, player: PlayerType) -> GameState {
+        let classes: Vec<ShipType> = state.ships(&player).iter().map(|ship| ship.class.clone()).collect();
+        let mut state = state;
+        for (row, class) in classes.into_iter().enumerate() {
+            state = place(state, player, class, Location{row: row as u32, col: 0}, Direction::Right).unwrap();
+        }
+        state
+    }
+
+    // a 4x4 grid where each player has Destroyers on rows 0 and 1 and a Torpedo Boat on row 2
+    fn small_config() -> GameConfig {
+        let fleet = FleetSpec::new(vec![(ShipType::Destroyer, 2), (ShipType::TorpedoBoat, 1)]);
+        GameConfig { fleet, ..GameConfig::with_grid(4, 4) }
+    }
+
+    fn started_game(config: GameConfig) -> GameState {
+        let state = place_fleet(GameState::with_config(config).unwrap(), PlayerType::Player1);
+        place_fleet(state, PlayerType::Player2)
+    }
+
+    fn salvo_game(shots_per_turn: ShotsPerTurn) -> GameState {
+        started_game(GameConfig { shots_per_turn, ..small_config() })
+    }
+
+    #[test]
+    fn salvo_turn_passes_after_the_whole_salvo() {
+        let state = salvo_game(ShotsPerTurn::ShipsAfloat);
+        assert_eq!(state.shots_this_turn(PlayerType::Player1), 3);
+        let salvo = [Location{row:0, col:0}, Location{row:0, col:1}, Location{row:3, col:3}];
+        let before = state.clone();
+        let state = state.fire_salvo(PlayerType::Player1, &salvo).unwrap();
+        let Some(GameEvent::SalvoFired { player, shots, .. }) = state.events().last() else { panic!("no salvo logged") };
+        // a salvo that hides the ship it sank is rejected
+        let result = before.apply(&GameEvent::SalvoFired { player: *player, shots: shots.clone(), sunk: Vec::new() });
+        assert!(matches!(result, Err(GameError::InvalidEvent { .. })));
+        assert!(state.turn.eq(&PlayerTurn::Player2));
+        assert_eq!(state.shots(&PlayerType::Player1).len(), 3);
+        assert!(state.messages.iter().any(|message| message == "Player 1 sunk Player 2's Destroyer!"));
+        assert_eq!(state.last_message().unwrap(), "Player 1 fires at (3, 3) and misses!");
+        assert!(matches!(state.events().last(), Some(GameEvent::SalvoFired { sunk, .. }) if sunk == &vec![ShipType::Destroyer]));
+        assert_eq!(GameState::from_events(state.events()).unwrap(), state);
+    }
+
+    #[test]
+    fn salvo_must_use_every_shot() {
+        let state = salvo_game(ShotsPerTurn::ShipsAfloat);
+        let result = state.fire(PlayerType::Player1, &Location{row:0, col:0});
+        assert_eq!(result.err().unwrap(), GameError::WrongSalvoSize { expected: 3, found: 1 });
+        let salvo = [Location{row:0, col:0}, Location{row:0, col:0}, Location{row:3, col:3}];
+        let result = state.fire_salvo(PlayerType::Player1, &salvo);
+        assert_eq!(result.err().unwrap(), GameError::AlreadyFired { location: Location{row:0, col:0} });
+        // a logged single shot can't get round the salvo size either
+        let result = state.apply(&GameEvent::ShotFired { player: PlayerType::Player1, location: Location{row:0, col:0}, hit: true, sunk: None });
+        assert!(matches!(result, Err(GameError::InvalidEvent { ref reason, .. }) if reason == "Shots are fired in salvos in this game"));
+
+        let state = GameState::new();
+        let result = state.fire_salvo(PlayerType::Player1, &[Location{row:0, col:0}, Location{row:0, col:1}]);
+        assert_eq!(result.err().unwrap(), GameError::WrongSalvoSize { expected: 1, found: 2 });
+        let shots = vec![Shot { location: Location{row:0, col:0}, hit: false }];
+        let result = state.apply(&GameEvent::SalvoFired { player: PlayerType::Player1, shots, sunk: Vec::new() });
+        assert!(matches!(result, Err(GameError::InvalidEvent { ref reason, .. }) if reason == "Shots are fired one at a time in this game"));
+    }
+
+    #[test]
+    fn salvo_shrinks_as_ships_sink() {
+        let state = salvo_game(ShotsPerTurn::ShipsAfloat);
+        let state = state.fire_salvo(PlayerType::Player1, &[Location{row:3, col:0}, Location{row:3, col:1}, Location{row:3, col:2}]).unwrap();
+        let state = state.fire_salvo(PlayerType::Player2, &[Location{row:2, col:0}, Location{row:3, col:1}, Location{row:3, col:2}]).unwrap();
+        assert_eq!(state.shots_this_turn(PlayerType::Player1), 2);
+        assert_eq!(state.shots_this_turn(PlayerType::Player2), 3);
+
+        let state = salvo_game(ShotsPerTurn::Fixed(20));
+        assert_eq!(state.shots_this_turn(PlayerType::Player1), 16);
+    }
+
+    #[test]
+    fn hit_keeps_the_turn() {
+        let state = started_game(GameConfig { keep_turn: KeepTurn::OnHit, ..small_config() });
+        let state = fire(state, PlayerType::Player1, &Location{row:0, col:0}).unwrap();
+        assert!(state.turn.eq(&PlayerTurn::Player1));
+        assert_eq!(state.last_message().unwrap(), "Player 1 fires again!");
+        let state = fire(state, PlayerType::Player1, &Location{row:3, col:3}).unwrap();
+        assert!(state.turn.eq(&PlayerTurn::Player2));
+        assert_eq!(GameState::from_events(state.events()).unwrap(), state);
+    }
+
+    #[test]
+    fn only_a_sink_keeps_the_turn() {
+        let state = started_game(GameConfig { keep_turn: KeepTurn::OnSink, ..small_config() });
+        let state = fire(state, PlayerType::Player1, &Location{row:0, col:0}).unwrap();
+        assert!(state.turn.eq(&PlayerTurn::Player2));
+        let state = fire(state, PlayerType::Player2, &Location{row:2, col:0}).unwrap();
+        assert!(state.turn.eq(&PlayerTurn::Player2));
+        assert_eq!(state.last_message().unwrap(), "Player 2 fires again!");
+    }
+
+    #[test]
+    fn winning_shot_ends_the_game_when_the_turn_is_kept() {
+        let state = started_game(GameConfig { keep_turn: KeepTurn::OnHit, ..small_config() });
+        let targets = [(0, 0), (0, 1), (1, 0), (1, 1), (2, 0)];
+        let state = targets.iter().fold(state, |state, (row, col)| fire(state, PlayerType::Player1, &Location{row: *row, col: *col}).unwrap());
+        assert!(state.result.eq(&GameResult::Player1Win));
+        assert!(state.turn.eq(&PlayerTurn::Neither));
+        assert_eq!(state.last_message().unwrap(), "Game over. Player 1 wins!");
+        assert!(!state.messages[state.messages.len() - 2].contains("fires again"));
+    }
+
+    #[test]
+    fn a_hit_in_a_salvo_keeps_the_turn() {
+        let state = started_game(GameConfig { shots_per_turn: ShotsPerTurn::Fixed(2), keep_turn: KeepTurn::OnHit, ..small_config() });
+        let state = state.fire_salvo(PlayerType::Player1, &[Location{row:3, col:3}, Location{row:0, col:0}]).unwrap();
+        assert!(state.turn.eq(&PlayerTurn::Player1));
+        let state = state.fire_salvo(PlayerType::Player1, &[Location{row:3, col:2}, Location{row:3, col:1}]).unwrap();
+        assert!(state.turn.eq(&PlayerTurn::Player2));
+    }
+
+    #[test]
+    fn game_stays_in_setup_until_both_players_are_ready() {
+        let state = place_fleet(GameState::new(), PlayerType::Player1);
+        assert!(state.result.eq(&GameResult::InSetup));
+        assert!(state.turn.eq(&PlayerTurn::Either));
+    }
+
+    #[test]
+    fn game_starts_when_all_ships_are_placed() {
+        let state = place_fleet(GameState::new(), PlayerType::Player1);
+        let state = place_fleet(state, PlayerType::Player2);
+        assert!(state.result.eq(&GameResult::InProgress));
+        assert!(state.turn.eq(&PlayerTurn::Player1));
+        let message = state.last_message().unwrap();
+        assert!(message.contains("Player 1 fires first!"));
+    }
+
+    #[test]
+    fn random_first_shooter_picks_a_player() {
+        let config = GameConfig { first_shooter: FirstShooter::Random, ..GameConfig::default() };
+        let state = place_fleet(GameState::with_config(config).unwrap(), PlayerType::Player1);
+        let state = place_fleet(state, PlayerType::Player2);
+        assert!(state.result.eq(&GameResult::InProgress));
+        assert!(state.turn.eq(&PlayerTurn::Player1) || state.turn.eq(&PlayerTurn::Player2));
+    }
+
+    #[test]
+    fn loser_of_last_game_fires_first_in_rematch() {
+        let config = GameConfig { first_shooter: FirstShooter::LoserOfLastGame, ..GameConfig::default() };
+        let mut state = GameState::with_config(config).unwrap();
+        state.result = GameResult::Player2Win;
+
+        let rematch = state.rematch();
+        assert!(rematch.result.eq(&GameResult::InSetup));
+        let rematch = place_fleet(rematch, PlayerType::Player2);
+        let rematch = place_fleet(rematch, PlayerType::Player1);
+        assert!(rematch.turn.eq(&PlayerTurn::Player1));
+
+        let mut state = rematch;
+        state.result = GameResult::Player1Win;
+        let rematch = place_fleet(state.rematch(), PlayerType::Player1);
+        let rematch = place_fleet(rematch, PlayerType::Player2);
+        assert!(rematch.turn.eq(&PlayerTurn::Player2));
+    }
+
+    #[test]
+    fn every_action_is_logged() {
+        let config = GameConfig { fleet: FleetSpec::new(vec![(ShipType::TorpedoBoat, 1)]), ..GameConfig::with_grid(2, 2) };
+        let state = GameState::with_config(config.clone()).unwrap();
+        let state = place(state, PlayerType::Player1, ShipType::TorpedoBoat, Location{row:0, col:0}, Direction::Up).unwrap();
+        let state = remove(&state, PlayerType::Player1, ShipType::TorpedoBoat, Location{row:0, col:0}).unwrap();
+        let state = place(state, PlayerType::Player1, ShipType::TorpedoBoat, Location{row:1, col:1}, Direction::Up).unwrap();
+        let state = place(state, PlayerType::Player2, ShipType::TorpedoBoat, Location{row:0, col:1}, Direction::Up).unwrap();
+        let state = fire(state, PlayerType::Player1, &Location{row:1, col:0}).unwrap();
+        let state = fire(state, PlayerType::Player2, &Location{row:1, col:1}).unwrap();
+        assert_eq!(state.events(), [
+            GameEvent::Created { config, last_loser: None },
+            GameEvent::ShipPlaced { player: PlayerType::Player1, class: ShipType::TorpedoBoat, location: Location{row:0, col:0}, direction: Direction::Up },
+            GameEvent::ShipRemoved { player: PlayerType::Player1, class: ShipType::TorpedoBoat, location: Location{row:0, col:0} },
+            GameEvent::ShipPlaced { player: PlayerType::Player1, class: ShipType::TorpedoBoat, location: Location{row:1, col:1}, direction: Direction::Up },
+            GameEvent::ShipPlaced { player: PlayerType::Player2, class: ShipType::TorpedoBoat, location: Location{row:0, col:1}, direction: Direction::Up },
+            GameEvent::GameStarted { first: PlayerType::Player1 },
+            GameEvent::ShotFired { player: PlayerType::Player1, location: Location{row:1, col:0}, hit: false, sunk: None },
+            GameEvent::ShotFired { player: PlayerType::Player2, location: Location{row:1, col:1}, hit: true, sunk: Some(ShipType::TorpedoBoat) },
+            GameEvent::GameOver { winner: PlayerType::Player2 },
+        ]);
+
+        // folding any prefix of the log gives the game as it was at that point
+        assert_eq!(GameState::from_events(state.events()).unwrap(), state);
+        let started = GameState::from_events(&state.events()[..6]).unwrap();
+        assert_eq!(started.result(), GameResult::InProgress);
+        assert_eq!(started.last_message().unwrap(), "All ships have been placed. Player 1 fires first!");
+    }
+
+    #[test]
+    fn events_are_checked_against_the_rules() {
+        let state = GameState::new();
+        let result = state.apply(&GameEvent::GameStarted { first: PlayerType::Player2 });
+        assert!(matches!(result, Err(GameError::InvalidEvent { .. })));
+        let result = state.apply(&GameEvent::ShotFired { player: PlayerType::Player1, location: Location{row:0, col:0}, hit: true, sunk: None });
+        assert_eq!(result.err().unwrap(), GameError::WrongPhase { expected: GameResult::InProgress, actual: GameResult::InSetup });
+        let result = GameState::from_events(&[GameEvent::GameOver { winner: PlayerType::Player1 }]);
+        assert!(matches!(result, Err(GameError::InvalidEvent { .. })));
+
+        // the log has to tell the truth about what each shot did
+        let state = place_fleet(state, PlayerType::Player1);
+        let mut events = place_fleet(state, PlayerType::Player2).events().to_vec();
+        let started = events.pop().unwrap();
+        let state = GameState::from_events(&events).unwrap();
+        let result = state.apply(&GameEvent::GameStarted { first: PlayerType::Player2 });
+        assert!(matches!(result, Err(GameError::InvalidEvent { ref reason, .. }) if reason == "Player 1 has to fire first"));
+        let state = state.apply(&started).unwrap();
+        let missed = GameEvent::ShotFired { player: PlayerType::Player1, location: Location{row:9, col:9}, hit: true, sunk: Some(ShipType::Carrier) };
+        let result = state.apply(&missed);
+        assert!(matches!(result, Err(GameError::InvalidEvent { ref reason, .. }) if reason == "The shot missed"));
+        let result = state.apply(&GameEvent::ShotFired { player: PlayerType::Player1, location: Location{row:0, col:0}, hit: false, sunk: None });
+        assert!(matches!(result, Err(GameError::InvalidEvent { ref reason, .. }) if reason == "The shot hit"));
+        assert!(state.apply(&GameEvent::ShotFired { player: PlayerType::Player1, location: Location{row:0, col:0}, hit: true, sunk: None }).is_ok());
+    }
+
+    #[test]
+    fn version_1_files_still_load() {
+        let state = GameState::new();
+        let serialized = state.to_string();
+        let version_1: Vec<&str> = serialized.lines().filter(|line| !line.starts_with("event|")).collect();
+        let version_1 = version_1.join("\n").replace("battleship|2", "battleship|1");
+        let loaded = GameState::from_str(&version_1).unwrap();
+        assert!(loaded.events().is_empty());
+        assert_eq!(loaded.messages(), state.messages());
+    }
+
+    #[test]
+    fn cant_remove_missing_ship() {
+        let state = GameState::new();
+        let result = remove(&state, PlayerType::Player1, ShipType::Carrier, Location{row:0, col:0});
+        assert_eq!(result.err().unwrap(), GameError::ShipNotFound { class: ShipType::Carrier, location: Location{row:0, col:0} });
+    }
+
+    #[test]
+    fn cant_remove_ships_after_game_has_started() {
+        let state = place_fleet(GameState::new(), PlayerType::Player1);
+        let state = place_fleet(state, PlayerType::Player2);
+        let class = state.ships(&PlayerType::Player1)[0].class.clone();
+        let result = remove(&state, PlayerType::Player1, class, Location{row:0, col:0});
+        assert!(result.is_err());
+        assert_eq!(result.err().unwrap(), GameError::WrongPhase { expected: GameResult::InSetup, actual: GameResult::InProgress });
+    }
+
+}
@@ -0,0 +1,36 @@
+//! Game logic for Battleship.
+//!
+//! A `GameState` is immutable from the outside: every action (`place`, `remove`, `fire`)
+//! returns the next state or an error describing why the action was rejected.
+
+pub mod ai;
+mod config;
+mod error;
+mod event;
+mod fleet;
+mod game;
+mod location;
+mod placer;
+mod player;
+pub mod referee;
+mod registry;
+mod render;
+mod replay;
+#[cfg(feature = "server")]
+pub mod server;
+mod ship;
+mod shot;
+pub mod simulation;
+
+pub use config::{FirstShooter, GameConfig, KeepTurn, NoTouch, ShotsPerTurn, DEFAULT_GRID_HEIGHT, DEFAULT_GRID_WIDTH, MAX_FLEET_SHIPS, MAX_GRID_SIZE};
+pub use error::GameError;
+pub use event::GameEvent;
+pub use fleet::FleetSpec;
+pub use game::{Direction, EnemyBoardCell, GameResult, GameState, OwnBoardCell};
+pub use location::{locations_from_string, locations_to_string, Location};
+pub use player::{PlayerTurn, PlayerType};
+pub use registry::ShipRegistry;
+pub use render::{render_enemy_board, render_own_board};
+pub use replay::Replay;
+pub use ship::{ships_from_str, ships_to_string, Ship, ShipClass, ShipType};
+pub use shot::{shots_from_str, shots_to_string, Shot};
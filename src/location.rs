@@ -1,21 +1,30 @@
 use std::fmt::Display;
+use std::str::FromStr;
+use crate::error::GameError;
 
-#[derive(Eq, PartialEq, Copy, Clone,Debug)]
+#[derive(Eq, PartialEq, Copy, Clone, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Location {
     pub row: u32,
     pub col: u32,
 }
 
 impl Location {
+    #[allow(clippy::inherent_to_string_shadow_display, clippy::wrong_self_convention)]
     pub fn to_string(&self) -> String {
-        return format!("{},{}",&self.col, &self.row);
+        format!("{},{}",&self.col, &self.row)
     }
-    pub fn from_str(input: &str) -> Result<Self, String> {
-        let parts = input.split(",").filter_map(|s|  s.parse::<u32>().ok()).collect::<Vec<u32>>();
+}
+
+impl FromStr for Location {
+    type Err = GameError;
+
+    fn from_str(input: &str) -> Result<Self, GameError> {
+        let parts = input.split(',').filter_map(|s|  s.parse::<u32>().ok()).collect::<Vec<u32>>();
         if parts.len() != 2 {
-            return Err(format!("Expected 2 , separated values but found {}", parts.len()));
+            return Err(GameError::parse(input, format!("Expected 2 , separated values but found {}", parts.len())));
         }
-        return Ok(Location{ row: parts[1], col: parts[0] })
+        Ok(Location{ row: parts[1], col: parts[0] })
     }
 }
 
@@ -25,31 +34,26 @@ impl Display for Location {
     }
 }
 
-pub fn locations_to_string(locations: &Vec<Location>) -> String {
-    return locations.iter().map(|l| l.to_string()).collect::<Vec<String>>().join(";");
+pub fn locations_to_string(locations: &[Location]) -> String {
+    locations.iter().map(|l| l.to_string()).collect::<Vec<String>>().join(";")
 }
 
-pub fn locations_from_string(serialized: &str) -> Result<Vec<Location>,String> {
+pub fn locations_from_string(serialized: &str) -> Result<Vec<Location>,GameError> {
     let locations: Vec<Location> = serialized
-        .split(";")
+        .split(';')
         .collect::<Vec<&str>>()
         .iter()
         .filter_map( | input: &&str | -> Option<Location> {
-            let result = Location::from_str(input);
-            return if result.is_ok() {
-                Some(result.unwrap())
-            } else {
-                None
-            }
+            Location::from_str(input).ok()
         }).collect();
 
     let expected_count = serialized
-        .split(";").count();
+        .split(';').count();
 
     if expected_count != locations.len() {
-        return Err(format!("Expected to deserialize {} locations but only deserialized {}", expected_count, locations.len()));
+        return Err(GameError::parse(serialized, format!("Expected to deserialize {} locations but only deserialized {}", expected_count, locations.len())));
     }
-    return Ok(locations);
+    Ok(locations)
 }
 
 #[cfg(test)]
@@ -93,4 +97,4 @@ mod tests {
         assert!(locations.is_ok());
         assert!(locations.unwrap().eq(&expected));
     }
-}
\ No newline at end of file
+}
@@ -1,16 +1,6 @@
-mod game;
-mod ship;
-mod location;
-mod player;
-mod shot;
-
+mod cli;
 
 fn main() {
-    //let operation = std::env::args().nth(1).expect("no pattern given");
-    // switch on operation - new, place, place
-    //let path = std::env::args().nth(2).expect("no path given");
-
-    println!("Hello, world!");
-
-    let game = game::GameState::new();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    std::process::exit(cli::run(&args));
 }
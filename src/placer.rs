@@ -0,0 +1,196 @@
+use rand::seq::SliceRandom;
+use rand::Rng;
+use crate::error::GameError;
+use crate::fleet::find_layout;
+use crate::game::{Direction, GameResult, GameState};
+use crate::location::Location;
+use crate::player::PlayerType;
+use crate::ship::ShipType;
+
+// how many placements `auto_place` tries before giving up on a layout
+const MAX_ATTEMPTS: usize = 20_000;
+
+/// Places every ship `player` hasn't placed yet at random, through `GameState::place` so the
+/// layout follows the same rules as placing by hand. Ships already on the board stay where
+/// they are. The same `rng` state always gives the same layout. When the random search runs
+/// out of attempts, the search `GameConfig::validate` uses to check the fleet fits takes
+/// over, so a valid fleet can always be placed on an empty board.
+pub fn auto_place<R: Rng + ?Sized>(game: &GameState, player: PlayerType, rng: &mut R) -> Result<GameState, GameError> {
+    if game.result() != GameResult::InSetup {
+        return Err(GameError::WrongPhase { expected: GameResult::InSetup, actual: game.result() });
+    }
+    let mut unplaced: Vec<ShipType> = game.ships(&player).iter()
+        .filter(|ship| ship.locations.is_empty())
+        .map(|ship| ship.class.clone())
+        .collect();
+    // the biggest ships are the hardest to fit, so they go first
+    unplaced.sort_by_key(|class| std::cmp::Reverse(class.size()));
+    let mut attempts = 0;
+    if let Some(placed) = place_remaining(game, player, &unplaced, rng, &mut attempts) {
+        return Ok(placed);
+    }
+    let taken: Vec<Location> = game.ships(&player).iter().flat_map(|ship| ship.locations.clone()).collect();
+    let layout = find_layout(&unplaced, &taken, game.width(), game.height(), game.config().no_touch)
+        .ok_or(GameError::NoLayout { player })?;
+    layout.into_iter().try_fold(game.clone(), |game, (class, location, direction)| game.place(player, class, location, direction))
+}
+
+// tries every placement of the first ship in random order, backtracking when the rest don't fit
+fn place_remaining<R: Rng + ?Sized>(game: &GameState, player: PlayerType, unplaced: &[ShipType], rng: &mut R, attempts: &mut usize) -> Option<GameState> {
+    let (class, rest) = match unplaced.split_first() {
+        Some(split) => split,
+        None => return Some(game.clone()),
+    };
+    let directions = distinct_directions(class);
+    let mut candidates: Vec<(Location, Direction)> = Vec::new();
+    for row in 0..game.height() {
+        for col in 0..game.width() {
+            for direction in directions.iter() {
+                candidates.push((Location { row, col }, *direction));
+            }
+        }
+    }
+    candidates.shuffle(rng);
+    for (location, direction) in candidates {
+        if *attempts == MAX_ATTEMPTS {
+            return None;
+        }
+        *attempts += 1;
+        if let Ok(next) = game.place(player, class.clone(), location, direction) {
+            if let Some(placed) = place_remaining(&next, player, rest, rng, attempts) {
+                return Some(placed);
+            }
+        }
+    }
+    None
+}
+
+// the directions that give the ship a different outline, a ship that looks the same turned
+// round covers the same cells from some other location, so one of them is enough
+fn distinct_directions(class: &ShipType) -> Vec<Direction> {
+    let mut outlines: Vec<Vec<(i64, i64)>> = Vec::new();
+    let mut directions = Vec::new();
+    for direction in [Direction::Up, Direction::Down, Direction::Left, Direction::Right] {
+        let footprint = class.footprint(direction);
+        let min_row = footprint.iter().map(|(row, _)| *row).min().unwrap_or(0);
+        let min_col = footprint.iter().map(|(_, col)| *col).min().unwrap_or(0);
+        let mut outline: Vec<(i64, i64)> = footprint.iter().map(|(row, col)| (row - min_row, col - min_col)).collect();
+        outline.sort();
+        if !outlines.contains(&outline) {
+            outlines.push(outline);
+            directions.push(direction);
+        }
+    }
+    directions
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use crate::config::{GameConfig, NoTouch};
+    use crate::error::GameError;
+    use crate::fleet::FleetSpec;
+    use crate::game::{Direction, GameResult, GameState};
+    use crate::location::Location;
+    use crate::placer::{auto_place, distinct_directions, place_remaining, MAX_ATTEMPTS};
+    use crate::player::PlayerType;
+    use crate::ship::ShipType;
+
+    fn cells(game: &GameState, player: PlayerType) -> Vec<Location> {
+        game.ships(&player).iter().flat_map(|ship| ship.locations.clone()).collect()
+    }
+
+    #[test]
+    fn places_the_whole_fleet() {
+        let game = auto_place(&GameState::new(), PlayerType::Player1, &mut StdRng::seed_from_u64(7)).unwrap();
+        assert!(game.ready(PlayerType::Player1));
+        let mut placed = cells(&game, PlayerType::Player1);
+        assert_eq!(placed.len() as u32, FleetSpec::original().cells());
+        placed.sort_by_key(|location| (location.row, location.col));
+        placed.dedup();
+        assert_eq!(placed.len() as u32, FleetSpec::original().cells());
+        assert!(placed.iter().all(|location| game.in_bounds(location)));
+    }
+
+    #[test]
+    fn same_seed_same_layout() {
+        let layout = |seed| cells(&auto_place(&GameState::new(), PlayerType::Player2, &mut StdRng::seed_from_u64(seed)).unwrap(), PlayerType::Player2);
+        assert_eq!(layout(42), layout(42));
+        assert_ne!(layout(42), layout(43));
+    }
+
+    #[test]
+    fn keeps_ships_already_placed_and_starts_the_game() {
+        let game = GameState::new();
+        let game = game.place(PlayerType::Player1, ShipType::Battleship, Location { row: 9, col: 0 }, Direction::Right).unwrap();
+        let game = auto_place(&game, PlayerType::Player1, &mut StdRng::seed_from_u64(1)).unwrap();
+        assert_eq!(game.ships(&PlayerType::Player1)[0].locations[0], Location { row: 9, col: 0 });
+        let game = auto_place(&game, PlayerType::Player2, &mut StdRng::seed_from_u64(1)).unwrap();
+        assert_eq!(game.result(), GameResult::InProgress);
+        assert!(matches!(auto_place(&game, PlayerType::Player2, &mut StdRng::seed_from_u64(1)), Err(GameError::WrongPhase { .. })));
+    }
+
+    #[test]
+    fn tight_fits_are_found() {
+        // the carriers only fit side by side, filling the board
+        let config = GameConfig { fleet: FleetSpec::new(vec![(ShipType::Carrier, 2)]), ..GameConfig::with_grid(5, 2) };
+        let game = GameState::with_config(config).unwrap();
+        let game = auto_place(&game, PlayerType::Player1, &mut StdRng::seed_from_u64(3)).unwrap();
+        assert_eq!(cells(&game, PlayerType::Player1).len(), 10);
+    }
+
+    #[test]
+    fn falls_back_to_the_full_search_when_the_attempts_run_out() {
+        // the Russian fleet only just fits on 7x7 without touching, too tight to find at random
+        let config = GameConfig { fleet: FleetSpec::russian(), no_touch: NoTouch::All, ..GameConfig::with_grid(7, 7) };
+        let game = GameState::with_config(config).unwrap();
+        let mut attempts = 0;
+        let unplaced: Vec<ShipType> = game.ships(&PlayerType::Player1).iter().map(|ship| ship.class.clone()).collect();
+        assert!(place_remaining(&game, PlayerType::Player1, &unplaced, &mut StdRng::seed_from_u64(1), &mut attempts).is_none());
+        let game = auto_place(&game, PlayerType::Player1, &mut StdRng::seed_from_u64(1)).unwrap();
+        assert!(game.ready(PlayerType::Player1));
+    }
+
+    #[test]
+    fn follows_the_no_touch_rule() {
+        let config = GameConfig { no_touch: NoTouch::All, ..GameConfig::default() };
+        for seed in 0..10 {
+            let game = auto_place(&GameState::with_config(config.clone()).unwrap(), PlayerType::Player1, &mut StdRng::seed_from_u64(seed)).unwrap();
+            let ships = game.ships(&PlayerType::Player1);
+            for (index, ship) in ships.iter().enumerate() {
+                for other in ships.iter().skip(index + 1) {
+                    let touches = ship.locations.iter().any(|a| other.locations.iter().any(|b| a.row.abs_diff(b.row) <= 1 && a.col.abs_diff(b.col) <= 1));
+                    assert!(!touches, "{} touches {} with seed {}", ship.class, other.class, seed);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn symmetric_ships_are_tried_one_way() {
+        assert_eq!(distinct_directions(&ShipType::TorpedoBoat), vec![Direction::Up]);
+        assert_eq!(distinct_directions(&ShipType::Carrier), vec![Direction::Up, Direction::Left]);
+    }
+
+    #[test]
+    fn search_stops_when_the_attempts_run_out() {
+        let game = GameState::new();
+        let classes = [ShipType::Carrier, ShipType::Destroyer];
+        let mut attempts = MAX_ATTEMPTS - 1;
+        assert!(place_remaining(&game, PlayerType::Player1, &classes, &mut StdRng::seed_from_u64(1), &mut attempts).is_none());
+        assert_eq!(attempts, MAX_ATTEMPTS);
+        let mut attempts = 0;
+        assert!(place_remaining(&game, PlayerType::Player1, &classes, &mut StdRng::seed_from_u64(1), &mut attempts).is_some());
+    }
+
+    #[test]
+    fn no_room_left_is_an_error() {
+        let config = GameConfig { fleet: FleetSpec::new(vec![(ShipType::TorpedoBoat, 2), (ShipType::Submarine, 1)]), ..GameConfig::with_grid(2, 2) };
+        let game = GameState::with_config(config).unwrap();
+        let game = game.place(PlayerType::Player1, ShipType::TorpedoBoat, Location { row: 0, col: 0 }, Direction::Right).unwrap();
+        let game = game.place(PlayerType::Player1, ShipType::TorpedoBoat, Location { row: 1, col: 1 }, Direction::Right).unwrap();
+        let result = auto_place(&game, PlayerType::Player1, &mut StdRng::seed_from_u64(3));
+        assert_eq!(result, Err(GameError::NoLayout { player: PlayerType::Player1 }));
+    }
+}
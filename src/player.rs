@@ -1,6 +1,9 @@
 use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use crate::error::GameError;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PlayerType {
     Player1,
     Player2
@@ -24,7 +27,20 @@ impl Display for PlayerType {
     }
 }
 
-#[derive(Clone,Eq,PartialEq)]
+impl FromStr for PlayerType {
+    type Err = GameError;
+
+    fn from_str(input: &str) -> Result<Self, GameError> {
+        match input {
+            "Player 1" => Ok(PlayerType::Player1),
+            "Player 2" => Ok(PlayerType::Player2),
+            _ => Err(GameError::parse(input, String::from("Unknown player"))),
+        }
+    }
+}
+
+#[derive(Clone,Eq,PartialEq,Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PlayerTurn {
     Player1,
     Player2,
@@ -51,3 +67,17 @@ impl Display for PlayerTurn {
         }
     }
 }
+
+impl FromStr for PlayerTurn {
+    type Err = GameError;
+
+    fn from_str(input: &str) -> Result<Self, GameError> {
+        match input {
+            "Player 1" => Ok(PlayerTurn::Player1),
+            "Player 2" => Ok(PlayerTurn::Player2),
+            "Either Player" => Ok(PlayerTurn::Either),
+            "Neither Player" => Ok(PlayerTurn::Neither),
+            _ => Err(GameError::parse(input, String::from("Unknown turn"))),
+        }
+    }
+}
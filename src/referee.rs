@@ -0,0 +1,435 @@
+//! A referee for bots playing over a line based text protocol, see docs/tcp.md.
+
+use std::fmt::Display;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
+use crate::config::{GameConfig, KeepTurn, NoTouch, ShotsPerTurn};
+use crate::error::GameError;
+use crate::game::{Direction, GameResult, GameState};
+use crate::location::Location;
+use crate::player::{PlayerTurn, PlayerType};
+use crate::ship::ShipType;
+
+/// How many rejected lines in a row a bot gets before it forfeits.
+pub const MAX_ERRORS: u32 = 3;
+
+/// How a refereed match ended.
+#[derive(Clone, Debug)]
+pub struct MatchResult {
+    pub winner: PlayerType,
+    /// Why the loser forfeited, `None` when every ship was sunk.
+    pub forfeit: Option<String>,
+    pub game: GameState,
+}
+
+/// Runs matches between two bots connected over TCP, enforcing the rules of `config`.
+pub struct Referee {
+    pub config: GameConfig,
+    /// How long a bot gets to answer before it forfeits.
+    pub timeout: Duration,
+}
+
+impl Default for Referee {
+    fn default() -> Self {
+        Referee { config: GameConfig::default(), timeout: Duration::from_secs(10) }
+    }
+}
+
+// one connected bot
+struct Bot {
+    player: PlayerType,
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Bot {
+    fn new(player: PlayerType, stream: TcpStream, timeout: Duration) -> io::Result<Bot> {
+        stream.set_read_timeout(Some(timeout))?;
+        Ok(Bot { player, reader: BufReader::new(stream.try_clone()?), writer: stream })
+    }
+
+    fn send(&mut self, line: &str) -> Result<(), String> {
+        writeln!(self.writer, "{}", line).and_then(|_| self.writer.flush())
+            .map_err(|e| format!("{} could not be sent to: {}", self.player, e))
+    }
+
+    fn receive(&mut self) -> Result<String, String> {
+        let mut line = String::new();
+        match self.reader.read_line(&mut line) {
+            Ok(0) => Err(format!("{} disconnected", self.player)),
+            Ok(_) => Ok(line.trim().to_string()),
+            Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) =>
+                Err(format!("{} took too long to answer", self.player)),
+            Err(e) => Err(format!("{} could not be read from: {}", self.player, e)),
+        }
+    }
+
+    // reads lines until `apply` accepts one, answering each rejected line with an error
+    fn until_accepted<T>(&mut self, mut apply: impl FnMut(&str) -> Result<T, String>) -> Result<T, String> {
+        for _ in 0..MAX_ERRORS {
+            let line = self.receive()?;
+            match apply(&line) {
+                Ok(accepted) => return Ok(accepted),
+                Err(error) => self.send(&format!("error {}", error))?,
+            }
+        }
+        Err(format!("{} sent {} rejected lines in a row", self.player, MAX_ERRORS))
+    }
+}
+
+// a forfeit names the player who lost it
+struct Forfeit {
+    player: PlayerType,
+    reason: String,
+}
+
+impl Referee {
+    pub fn new(config: GameConfig) -> Self {
+        Referee { config, ..Referee::default() }
+    }
+
+    /// Accepts bots on `listener` in pairs, the first of each pair plays as Player 1. Each
+    /// match runs on its own thread and `report` is called with its result, or with the error
+    /// if the match could not be played.
+    pub fn serve(&self, listener: TcpListener, report: impl Fn(Result<MatchResult, GameError>) + Clone + Send + 'static) -> io::Result<()> {
+        let mut waiting: Option<TcpStream> = None;
+        for stream in listener.incoming() {
+            let stream = stream?;
+            let first = match waiting.take() {
+                Some(first) => first,
+                None => {
+                    waiting = Some(stream);
+                    continue;
+                }
+            };
+            let referee = Referee { config: self.config.clone(), timeout: self.timeout };
+            let report = report.clone();
+            thread::spawn(move || report(referee.run([first, stream])));
+        }
+        Ok(())
+    }
+
+    /// Plays one match between the two bots and tells both how it ended. The protocol has no
+    /// salvos, so a config with more than one shot a turn is rejected.
+    pub fn run(&self, bots: [TcpStream; 2]) -> Result<MatchResult, GameError> {
+        if self.config.shots_per_turn != ShotsPerTurn::Single {
+            return Err(GameError::InvalidConfig { reason: String::from("The referee only plays games with one shot a turn") });
+        }
+        let game = GameState::with_config(self.config.clone())?;
+        let [first, second] = bots;
+        let mut bots = match (Bot::new(PlayerType::Player1, first, self.timeout), Bot::new(PlayerType::Player2, second, self.timeout)) {
+            (Ok(first), Ok(second)) => [first, second],
+            (Err(_), _) => return Ok(self.finish(game, None, PlayerType::Player2, Some(String::from("Player 1 could not be set up")))),
+            (_, Err(_)) => return Ok(self.finish(game, None, PlayerType::Player1, Some(String::from("Player 2 could not be set up")))),
+        };
+        let mut game = game;
+        Ok(match play(&mut game, &mut bots) {
+            Ok(()) => {
+                let winner = match game.result() {
+                    GameResult::Player2Win => PlayerType::Player2,
+                    _ => PlayerType::Player1,
+                };
+                self.finish(game, Some(&mut bots), winner, None)
+            }
+            Err(forfeit) => self.finish(game, Some(&mut bots), PlayerType::other(&forfeit.player), Some(forfeit.reason)),
+        })
+    }
+
+    fn finish(&self, game: GameState, bots: Option<&mut [Bot; 2]>, winner: PlayerType, forfeit: Option<String>) -> MatchResult {
+        for bot in bots.into_iter().flatten() {
+            let outcome = if bot.player == winner { "win" } else { "lose" };
+            let _ = bot.send(&format!("game_over {}", outcome));
+        }
+        MatchResult { winner, forfeit, game }
+    }
+}
+
+fn seat(player: PlayerType) -> usize {
+    match player {
+        PlayerType::Player1 => 0,
+        PlayerType::Player2 => 1,
+    }
+}
+
+// plays the game to the end, or stops at the first forfeit
+fn play(game: &mut GameState, bots: &mut [Bot; 2]) -> Result<(), Forfeit> {
+    let setup = setup_line(game.config());
+    for bot in bots.iter_mut() {
+        let player = bot.player;
+        bot.send(&setup)
+            .and_then(|_| place_fleet(bot, game))
+            .map_err(|reason| Forfeit { player, reason })?;
+    }
+
+    while game.result() == GameResult::InProgress {
+        let player = match game.turn() {
+            PlayerTurn::Player2 => PlayerType::Player2,
+            _ => PlayerType::Player1,
+        };
+        let shooter = &mut bots[seat(player)];
+        let fired = shooter.send("your_turn").and_then(|_| shooter.until_accepted(|line| {
+            let location = match line.strip_prefix("fire ") {
+                Some(location) => parse_location(location)?,
+                None => return Err(format!("expected fire <row>,<col>, found {}", line)),
+            };
+            game.fire(player, &location).map(|next| (location, next)).map_err(|e| protocol_error(&e))
+        }));
+        let (location, next) = fired.map_err(|reason| Forfeit { player, reason })?;
+        *game = next;
+        let result = shot_result(game, player, &location);
+        bots[seat(player)].send(&format!("result {}", result))
+            .map_err(|reason| Forfeit { player, reason })?;
+        let opponent = PlayerType::other(&player);
+        bots[seat(opponent)].send(&format!("opponent_fire {} {}", format_location(&location), result))
+            .map_err(|reason| Forfeit { player: opponent, reason })?;
+    }
+    Ok(())
+}
+
+// the board size, any optional rules that are switched on, and the fleet, which goes last as
+// class names may contain spaces
+fn setup_line(config: &GameConfig) -> String {
+    let mut setup = format!("setup {}x{}", config.width, config.height);
+    if config.keep_turn != KeepTurn::Never {
+        setup.push_str(&format!(" again {}", config.keep_turn));
+    }
+    if config.no_touch != NoTouch::Off {
+        setup.push_str(&format!(" notouch {}", config.no_touch));
+    }
+    format!("{} fleet {}", setup, config.fleet.to_string())
+}
+
+fn place_fleet(bot: &mut Bot, game: &mut GameState) -> Result<(), String> {
+    let player = bot.player;
+    while game.result() == GameResult::InSetup && !game.ready(player) {
+        *game = bot.until_accepted(|line| {
+            let (class, location, direction) = parse_placement(line, game)?;
+            game.place(player, class, location, direction).map_err(|e| protocol_error(&e))
+        })?;
+        bot.send("ok")?;
+    }
+    Ok(())
+}
+
+// `place <class> <row>,<col> <direction>`, the class name may contain spaces
+fn parse_placement(line: &str, game: &GameState) -> Result<(ShipType, Location, Direction), String> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    match words.as_slice() {
+        ["place", class @ .., location, direction] if !class.is_empty() => Ok((
+            game.config().registry.parse(&class.join(" ")).map_err(|e| e.to_string())?,
+            parse_location(location)?,
+            Direction::from_str(direction).map_err(|e| e.to_string())?,
+        )),
+        _ => Err(format!("expected place <class> <row>,<col> <direction>, found {}", line)),
+    }
+}
+
+// the protocol writes locations row first, unlike `Location`'s own string format
+fn parse_location(input: &str) -> Result<Location, String> {
+    let parsed = input.trim().split_once(',').map(|(row, col)| (row.trim().parse::<u32>(), col.trim().parse::<u32>()));
+    match parsed {
+        Some((Ok(row), Ok(col))) => Ok(Location { row, col }),
+        _ => Err(format!("expected <row>,<col>, found {}", input)),
+    }
+}
+
+fn format_location(location: &Location) -> String {
+    format!("{},{}", location.row, location.col)
+}
+
+// the error's message with its locations written `row,col`, errors without a location read as
+// they display
+fn protocol_error(error: &GameError) -> String {
+    match error {
+        GameError::OutOfBounds { location, width, height } =>
+            format!("Invalid fire coordinates {}, must be between 0,0 and {},{}.", format_location(location), height - 1, width - 1),
+        GameError::NoRoom { class, location, direction } =>
+            format!("Not enough room to place a {} at {} {}", class, format_location(location), direction),
+        GameError::AlreadyFired { location } =>
+            format!("Cannot fire on {}, you have already fired there!", format_location(location)),
+        GameError::Overlap { location, direction } =>
+            format!("Cannot place a ship at {} {}, as it would overlap another ship.", format_location(location), direction),
+        GameError::Touching { location, direction, touching } =>
+            format!("Cannot place a ship at {} {}, as it would touch your {}.", format_location(location), direction, touching),
+        GameError::ShipNotFound { class, location } =>
+            format!("Could not find a {} at {}", class, format_location(location)),
+        _ => error.to_string(),
+    }
+}
+
+/// What a shot did: `hit`, `miss` or `sunk <class>`.
+enum ShotResult {
+    Hit,
+    Miss,
+    Sunk(String),
+}
+
+impl Display for ShotResult {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> std::result::Result<(), ::std::fmt::Error> {
+        match self {
+            ShotResult::Hit => f.write_str("hit"),
+            ShotResult::Miss => f.write_str("miss"),
+            ShotResult::Sunk(class) => write!(f, "sunk {}", class),
+        }
+    }
+}
+
+fn shot_result(game: &GameState, player: PlayerType, location: &Location) -> ShotResult {
+    let ship = game.ships(&PlayerType::other(&player)).iter().find(|ship| ship.locations.contains(location));
+    match ship {
+        Some(ship) if ship.sunk() => ShotResult::Sunk(ship.class.to_string()),
+        Some(_) => ShotResult::Hit,
+        None => ShotResult::Miss,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
+    use crate::config::{GameConfig, KeepTurn, NoTouch, ShotsPerTurn};
+    use crate::error::GameError;
+    use crate::fleet::FleetSpec;
+    use crate::game::Direction;
+    use crate::player::PlayerType;
+    use crate::referee::{parse_location, protocol_error, setup_line, Referee};
+    use crate::location::Location;
+    use crate::ship::ShipType;
+
+    fn referee() -> Referee {
+        let config = GameConfig { fleet: FleetSpec::new(vec![(ShipType::Destroyer, 1)]), ..GameConfig::with_grid(3, 3) };
+        Referee { config, timeout: Duration::from_secs(5) }
+    }
+
+    // a connected pair of streams, the referee's end first
+    fn pipe() -> (TcpStream, TcpStream) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let bot = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        (listener.accept().unwrap().0, bot)
+    }
+
+    // places its destroyer in the top left corner then fires at every cell in turn, answering
+    // with `script` first, and returns everything the referee sent it. Errors are only
+    // answered from the script.
+    fn bot(stream: TcpStream, script: Vec<&'static str>) -> thread::JoinHandle<Vec<String>> {
+        thread::spawn(move || {
+            let mut writer = stream.try_clone().unwrap();
+            let mut script = script.into_iter();
+            let mut targets = (0..3).flat_map(|row| (0..3).map(move |col| format!("fire {},{}", row, col)));
+            let mut received = Vec::new();
+            for line in BufReader::new(stream).lines() {
+                let line = line.unwrap();
+                received.push(line.clone());
+                let reply = match line.split_whitespace().next() {
+                    Some("setup") => script.next().map(String::from).unwrap_or_else(|| String::from("place Destroyer 0,0 right")),
+                    Some("your_turn") => script.next().map(String::from).unwrap_or_else(|| targets.next().unwrap()),
+                    Some("error") => match script.next() {
+                        Some(reply) => String::from(reply),
+                        None => continue,
+                    },
+                    Some("game_over") => break,
+                    _ => continue,
+                };
+                writeln!(writer, "{}", reply).unwrap();
+            }
+            received
+        })
+    }
+
+    #[test]
+    fn referee_a_match() {
+        let (p1, p1_bot) = pipe();
+        let (p2, p2_bot) = pipe();
+        let p1_bot = bot(p1_bot, vec![]);
+        let p2_bot = bot(p2_bot, vec!["place Destroyer 2,1 down", "place Destroyer 0,0 down"]);
+
+        let result = referee().run([p1, p2]).unwrap();
+        assert_eq!(result.winner, PlayerType::Player2);
+        assert!(result.forfeit.is_none());
+        assert_eq!(p1_bot.join().unwrap(), [
+            "setup 3x3 fleet Destroyer:1", "ok",
+            "your_turn", "result hit", "opponent_fire 0,0 hit",
+            "your_turn", "result miss", "opponent_fire 0,1 sunk Destroyer",
+            "game_over lose",
+        ]);
+        assert_eq!(p2_bot.join().unwrap(), [
+            "setup 3x3 fleet Destroyer:1", "error Not enough room to place a Destroyer at 2,1 Down", "ok",
+            "opponent_fire 0,0 hit", "your_turn", "result hit",
+            "opponent_fire 0,1 miss", "your_turn", "result sunk Destroyer",
+            "game_over win",
+        ]);
+    }
+
+    #[test]
+    fn too_many_rejected_lines_forfeit() {
+        let (p1, p1_bot) = pipe();
+        let (p2, p2_bot) = pipe();
+        let p1_bot = bot(p1_bot, vec!["place Destroyer 0,0 right", "fire", "fire 9,9", "launch"]);
+        let p2_bot = bot(p2_bot, vec![]);
+
+        let result = referee().run([p1, p2]).unwrap();
+        assert_eq!(result.winner, PlayerType::Player2);
+        assert_eq!(result.forfeit.as_deref(), Some("Player 1 sent 3 rejected lines in a row"));
+        assert_eq!(p1_bot.join().unwrap().last().unwrap(), "game_over lose");
+        assert_eq!(p2_bot.join().unwrap().last().unwrap(), "game_over win");
+    }
+
+    #[test]
+    fn silent_bots_forfeit() {
+        let (p1, _p1_bot) = pipe();
+        let (p2, p2_bot) = pipe();
+        let p2_bot = bot(p2_bot, vec![]);
+        let result = Referee { timeout: Duration::from_millis(50), ..referee() }.run([p1, p2]).unwrap();
+        assert_eq!(result.winner, PlayerType::Player2);
+        assert_eq!(result.forfeit.as_deref(), Some("Player 1 took too long to answer"));
+        assert_eq!(p2_bot.join().unwrap(), ["game_over win"]);
+    }
+
+    #[test]
+    fn salvo_games_are_rejected() {
+        let (p1, _p1_bot) = pipe();
+        let (p2, _p2_bot) = pipe();
+        let mut referee = referee();
+        referee.config.shots_per_turn = ShotsPerTurn::ShipsAfloat;
+        assert!(matches!(referee.run([p1, p2]), Err(GameError::InvalidConfig { .. })));
+    }
+
+    #[test]
+    fn matches_that_cannot_be_played_are_reported() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let mut referee = referee();
+        referee.config.shots_per_turn = ShotsPerTurn::ShipsAfloat;
+        let (sender, results) = mpsc::channel();
+        thread::spawn(move || referee.serve(listener, move |result| sender.send(result).unwrap()));
+        let _bots = [TcpStream::connect(addr).unwrap(), TcpStream::connect(addr).unwrap()];
+        let result = results.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(matches!(result, Err(GameError::InvalidConfig { .. })));
+    }
+
+    #[test]
+    fn setup_names_the_optional_rules() {
+        let config = referee().config;
+        assert_eq!(setup_line(&config), "setup 3x3 fleet Destroyer:1");
+        let config = GameConfig { keep_turn: KeepTurn::OnHit, no_touch: NoTouch::All, ..config };
+        assert_eq!(setup_line(&config), "setup 3x3 again hit notouch all fleet Destroyer:1");
+    }
+
+    #[test]
+    fn locations_are_row_first() {
+        assert_eq!(parse_location("1, 2"), Ok(Location { row: 1, col: 2 }));
+        assert!(parse_location("1;2").is_err());
+        let error = GameError::OutOfBounds { location: Location { row: 1, col: 12 }, width: 10, height: 8 };
+        assert_eq!(protocol_error(&error), "Invalid fire coordinates 1,12, must be between 0,0 and 7,9.");
+        let error = GameError::Touching { location: Location { row: 2, col: 5 }, direction: Direction::Down, touching: ShipType::Destroyer };
+        assert_eq!(protocol_error(&error), "Cannot place a ship at 2,5 Down, as it would touch your Destroyer.");
+        let error = GameError::NotYourTurn { player: PlayerType::Player2 };
+        assert_eq!(protocol_error(&error), error.to_string());
+    }
+}
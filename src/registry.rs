@@ -0,0 +1,167 @@
+use std::collections::HashSet;
+use std::str::FromStr;
+use crate::error::GameError;
+use crate::location::Location;
+use crate::ship::{ShipClass, ShipType};
+
+// characters used as separators by the serialized formats
+const RESERVED: [char; 7] = ['|', '&', ';', ',', ':', '\n', '\r'];
+
+/// The ship classes a game knows about, looked up by name.
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ShipRegistry {
+    classes: Vec<ShipType>,
+}
+
+impl Default for ShipRegistry {
+    fn default() -> Self {
+        ShipRegistry::standard()
+    }
+}
+
+// names are matched ignoring case and spaces, so "torpedoboat" finds the "Torpedo Boat"
+fn normalize(name: &str) -> String {
+    name.chars().filter(|c| !c.is_whitespace()).flat_map(|c| c.to_lowercase()).collect()
+}
+
+impl ShipRegistry {
+    /// Just the built in classes.
+    pub fn standard() -> Self {
+        ShipRegistry {
+            classes: vec![
+                ShipType::Carrier,
+                ShipType::Battleship,
+                ShipType::Cruiser,
+                ShipType::Submarine,
+                ShipType::Destroyer,
+                ShipType::TorpedoBoat,
+            ],
+        }
+    }
+
+    pub fn classes(&self) -> &Vec<ShipType> {
+        &self.classes
+    }
+
+    /// The classes that were added with `register`.
+    pub fn custom_classes(&self) -> Vec<&ShipClass> {
+        self.classes.iter().filter_map(|class| match class {
+            ShipType::Custom(class) => Some(class),
+            _ => None,
+        }).collect()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ShipType> {
+        let name = normalize(name);
+        self.classes.iter().find(|class| normalize(&class.to_string()) == name)
+    }
+
+    pub fn contains(&self, class: &ShipType) -> bool {
+        self.get(&class.to_string()) == Some(class)
+    }
+
+    pub fn parse(&self, name: &str) -> Result<ShipType, GameError> {
+        match self.get(name) {
+            Some(class) => Ok(class.clone()),
+            None => Err(GameError::parse(name, String::from("Unknown ship class"))),
+        }
+    }
+
+    /// Adds a custom class, returning the `ShipType` to use for it.
+    pub fn register(&mut self, class: ShipClass) -> Result<ShipType, GameError> {
+        let invalid = |reason: &str| GameError::InvalidShipClass { name: class.name.clone(), reason: String::from(reason) };
+        if class.name.trim().is_empty() || class.name.contains(RESERVED) {
+            return Err(invalid("Name must not be empty or contain any of | & ; , :"));
+        }
+        if self.get(&class.name).is_some() {
+            return Err(invalid("A class with this name already exists"));
+        }
+        if class.length == 0 {
+            return Err(invalid("Length must be at least 1"));
+        }
+        if let Some(shape) = &class.shape {
+            let cells: HashSet<&Location> = shape.iter().collect();
+            if cells.len() != shape.len() || shape.len() != class.length as usize {
+                return Err(invalid("Shape must list as many distinct cells as the length"));
+            }
+            if !cells.contains(&Location { row: 0, col: 0 }) {
+                return Err(invalid("Shape must include the cell at 0,0"));
+            }
+        }
+        let ship_type = ShipType::Custom(class);
+        self.classes.push(ship_type.clone());
+        Ok(ship_type)
+    }
+
+    /// The custom class definitions, one per line, in the format read by `from_str`.
+    #[allow(clippy::inherent_to_string)]
+    pub fn to_string(&self) -> String {
+        self.custom_classes().iter().map(|class| class.to_string()).collect::<Vec<String>>().join("\n")
+    }
+}
+
+impl FromStr for ShipRegistry {
+    type Err = GameError;
+
+    /// Reads custom class definitions, one `name|length` or `name|length|shape` per line,
+    /// on top of the built in classes.
+    fn from_str(input: &str) -> Result<Self, GameError> {
+        let mut registry = ShipRegistry::standard();
+        for line in input.lines().map(|line| line.trim()).filter(|line| !line.is_empty()) {
+            registry.register(ShipClass::from_str(line)?)?;
+        }
+        Ok(registry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use crate::error::GameError;
+    use crate::location::Location;
+    use crate::registry::ShipRegistry;
+    use crate::ship::{Ship, ShipClass, ShipType};
+
+    #[test]
+    fn standard_classes_are_registered() {
+        let registry = ShipRegistry::standard();
+        assert_eq!(registry.parse("Carrier").unwrap(), ShipType::Carrier);
+        assert_eq!(registry.parse("torpedo boat").unwrap(), ShipType::TorpedoBoat);
+        assert!(registry.custom_classes().is_empty());
+        assert!(registry.parse("Dreadnought").is_err());
+    }
+
+    #[test]
+    fn register_custom_class() {
+        let mut registry = ShipRegistry::standard();
+        let dreadnought = registry.register(ShipClass::new("Dreadnought", 6)).unwrap();
+        assert_eq!(dreadnought.size(), 6);
+        assert_eq!(dreadnought.to_string(), "Dreadnought");
+        assert_eq!(registry.parse("Dreadnought").unwrap(), dreadnought);
+        assert!(registry.contains(&dreadnought));
+
+        let ship = Ship::parse("Dreadnought|0|0,0;1,0;2,0;3,0;4,0;5,0", &registry).unwrap();
+        assert_eq!(ship.class, dreadnought);
+    }
+
+    #[test]
+    fn rejects_invalid_classes() {
+        let mut registry = ShipRegistry::standard();
+        assert!(matches!(registry.register(ShipClass::new("Carrier", 3)), Err(GameError::InvalidShipClass { .. })));
+        assert!(matches!(registry.register(ShipClass::new("Row|boat", 1)), Err(GameError::InvalidShipClass { .. })));
+        assert!(matches!(registry.register(ShipClass::new("Raft", 0)), Err(GameError::InvalidShipClass { .. })));
+        let off_origin = ShipClass::shaped("Hook", vec![Location{row: 0, col: 1}, Location{row: 1, col: 1}]);
+        assert!(matches!(registry.register(off_origin), Err(GameError::InvalidShipClass { .. })));
+    }
+
+    #[test]
+    fn registry_from_definitions() {
+        let registry = ShipRegistry::from_str("Dreadnought|6\nPatrol Boat|1\nHook|3|0,0;1,0;1,1\n").unwrap();
+        assert_eq!(registry.custom_classes().len(), 3);
+        assert_eq!(registry.parse("Patrol Boat").unwrap().size(), 1);
+        let hook = registry.parse("Hook").unwrap();
+        assert_eq!(hook.size(), 3);
+        assert_eq!(ShipRegistry::from_str(&registry.to_string()).unwrap(), registry);
+    }
+}
@@ -0,0 +1,68 @@
+use crate::game::{EnemyBoardCell, OwnBoardCell};
+
+/// Draws a board from `GameState::as_own_board` as text, one row per line:
+/// `#` ship, `X` hit ship, `o` miss and `.` open water.
+pub fn render_own_board(board: &[Vec<OwnBoardCell>]) -> String {
+    render(board, |cell| match cell {
+        OwnBoardCell::Empty => '.',
+        OwnBoardCell::Ship => '#',
+        OwnBoardCell::ShipHit => 'X',
+        OwnBoardCell::Miss => 'o',
+    })
+}
+
+/// Draws a board from `GameState::as_enemy_board` as text, one row per line:
+/// `X` hit, `#` sunk ship, `o` miss and `.` not fired at yet.
+pub fn render_enemy_board(board: &[Vec<EnemyBoardCell>]) -> String {
+    render(board, |cell| match cell {
+        EnemyBoardCell::Empty => '.',
+        EnemyBoardCell::Hit => 'X',
+        EnemyBoardCell::Sunk => '#',
+        EnemyBoardCell::Miss => 'o',
+    })
+}
+
+// column numbers across the top and row numbers down the side, so cells can be
+// read off as the col,row used by `Location::from_str`
+fn render<T>(board: &[Vec<T>], symbol: impl Fn(&T) -> char) -> String {
+    let width = board.first().map(|row| row.len()).unwrap_or(0);
+    let mut lines: Vec<String> = Vec::new();
+    let header: String = (0..width).map(|col| format!("{:>3}", col)).collect();
+    lines.push(format!("   {}", header));
+    for (row, cells) in board.iter().enumerate() {
+        let cells: String = cells.iter().map(|cell| format!("{:>3}", symbol(cell))).collect();
+        lines.push(format!("{:>3}{}", row, cells));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::{EnemyBoardCell, OwnBoardCell};
+    use crate::render::{render_enemy_board, render_own_board};
+
+    #[test]
+    fn own_board() {
+        let board = vec![
+            vec![OwnBoardCell::Ship, OwnBoardCell::ShipHit, OwnBoardCell::Empty],
+            vec![OwnBoardCell::Miss, OwnBoardCell::Empty, OwnBoardCell::Empty],
+        ];
+        assert_eq!(render_own_board(&board), [
+            "     0  1  2",
+            "  0  #  X  .",
+            "  1  o  .  .",
+        ].join("\n"));
+    }
+
+    #[test]
+    fn enemy_board_with_two_digit_columns() {
+        let mut board = vec![vec![EnemyBoardCell::Empty; 11]];
+        board[0][10] = EnemyBoardCell::Sunk;
+        board[0][0] = EnemyBoardCell::Hit;
+        board[0][1] = EnemyBoardCell::Miss;
+        assert_eq!(render_enemy_board(&board), [
+            "     0  1  2  3  4  5  6  7  8  9 10",
+            "  0  X  o  .  .  .  .  .  .  .  .  #",
+        ].join("\n"));
+    }
+}
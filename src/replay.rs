@@ -0,0 +1,176 @@
+//! Recorded games that can be stepped through shot by shot.
+
+use std::str::FromStr;
+use crate::error::GameError;
+use crate::event::GameEvent;
+use crate::game::GameState;
+use crate::registry::ShipRegistry;
+use crate::ship::ShipClass;
+
+// bumped whenever the replay file format changes
+const REPLAY_VERSION: u32 = 1;
+
+/// A game's history split into moves. Move 0 is the game as it stood before the first shot,
+/// with both fleets in place, and every later move is one more shot, or one more salvo under
+/// the Salvo rules.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Replay {
+    events: Vec<GameEvent>,
+    // where each shot or salvo sits in `events`
+    shots: Vec<usize>,
+}
+
+impl Replay {
+    /// Records `game` from its event log.
+    pub fn new(game: &GameState) -> Result<Self, GameError> {
+        Replay::from_events(game.events().to_vec())
+    }
+
+    /// Checks the events make up a game before keeping them.
+    pub fn from_events(events: Vec<GameEvent>) -> Result<Self, GameError> {
+        GameState::from_events(&events)?;
+        let shots = events.iter().enumerate()
+            .filter(|(_, event)| matches!(event, GameEvent::ShotFired { .. } | GameEvent::SalvoFired { .. }))
+            .map(|(index, _)| index)
+            .collect();
+        Ok(Replay { events, shots })
+    }
+
+    /// The number of shots or salvos in the game, so the last move.
+    pub fn moves(&self) -> usize {
+        self.shots.len()
+    }
+
+    /// The game as it stood after `step` moves, `step` past the last move gives the final game.
+    pub fn at(&self, step: usize) -> GameState {
+        let end = match self.shots.get(step) {
+            Some(next_shot) => *next_shot,
+            None => self.events.len(),
+        };
+        GameState::from_events(&self.events[..end]).expect("replays are checked when they are made")
+    }
+
+    /// The shot or salvo taken in move `step`, counting from 1.
+    pub fn shot(&self, step: usize) -> Option<&GameEvent> {
+        self.shots.get(step.checked_sub(1)?).map(|index| &self.events[*index])
+    }
+
+    /// Serializes the replay, one record per line: the custom ship classes, then the events.
+    #[allow(clippy::inherent_to_string)]
+    pub fn to_string(&self) -> String {
+        let mut lines = vec![format!("battleship-replay|{}", REPLAY_VERSION)];
+        let registries = self.events.iter().filter_map(|event| match event {
+            GameEvent::Created { config, .. } => Some(&config.registry),
+            _ => None,
+        });
+        let mut classes: Vec<&ShipClass> = Vec::new();
+        for class in registries.flat_map(|registry| registry.custom_classes()) {
+            if !classes.contains(&class) {
+                classes.push(class);
+            }
+        }
+        for class in classes {
+            lines.push(format!("class|{}", class.to_string()));
+        }
+        for event in self.events.iter() {
+            lines.push(format!("event|{}", event.to_string()));
+        }
+        lines.join("\n")
+    }
+}
+
+impl FromStr for Replay {
+    type Err = GameError;
+
+    fn from_str(input: &str) -> Result<Self, GameError> {
+        let mut lines = input.lines().filter(|line| !line.is_empty());
+        if lines.next() != Some(format!("battleship-replay|{}", REPLAY_VERSION).as_str()) {
+            return Err(GameError::parse(input, format!("Expected battleship-replay|{} header", REPLAY_VERSION)));
+        }
+        let mut registry = ShipRegistry::standard();
+        let mut events = Vec::new();
+        for line in lines {
+            match line.split_once('|') {
+                Some(("class", class)) => { registry.register(ShipClass::from_str(class)?)?; }
+                Some(("event", event)) => events.push(GameEvent::parse(event, &registry)?),
+                _ => return Err(GameError::parse(line, String::from("Expected a class or event record"))),
+            }
+        }
+        Replay::from_events(events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use crate::config::GameConfig;
+    use crate::error::GameError;
+    use crate::event::GameEvent;
+    use crate::fleet::FleetSpec;
+    use crate::game::{Direction, GameResult, GameState};
+    use crate::location::Location;
+    use crate::player::PlayerType;
+    use crate::registry::ShipRegistry;
+    use crate::replay::Replay;
+    use crate::ship::ShipClass;
+
+    // a game on a 3x3 board with one custom two cell ship each, won by Player 1 in their third shot
+    fn finished_game() -> GameState {
+        let mut registry = ShipRegistry::standard();
+        let dinghy = registry.register(ShipClass::new("Dinghy", 2)).unwrap();
+        let config = GameConfig { registry, fleet: FleetSpec::new(vec![(dinghy.clone(), 1)]), ..GameConfig::with_grid(3, 3) };
+        let game = GameState::with_config(config).unwrap();
+        let game = game.place(PlayerType::Player1, dinghy.clone(), Location { row: 0, col: 0 }, Direction::Right).unwrap();
+        let game = game.place(PlayerType::Player2, dinghy, Location { row: 2, col: 1 }, Direction::Right).unwrap();
+        let shots = [(2, 0), (1, 1), (2, 1), (1, 2), (2, 2)];
+        shots.iter().enumerate().fold(game, |game, (index, (row, col))| {
+            let player = if index % 2 == 0 { PlayerType::Player1 } else { PlayerType::Player2 };
+            game.fire(player, &Location { row: *row, col: *col }).unwrap()
+        })
+    }
+
+    #[test]
+    fn steps_through_the_shots() {
+        let game = finished_game();
+        let replay = Replay::new(&game).unwrap();
+        assert_eq!(replay.moves(), 5);
+
+        let start = replay.at(0);
+        assert_eq!(start.result(), GameResult::InProgress);
+        assert!(start.shots(&PlayerType::Player1).is_empty());
+        assert_eq!(start.ships(&PlayerType::Player2)[0].locations, vec![Location { row: 2, col: 1 }, Location { row: 2, col: 2 }]);
+
+        assert_eq!(replay.at(2).shots(&PlayerType::Player2).len(), 1);
+        assert_eq!(replay.at(5), game);
+        assert_eq!(replay.at(99), game);
+        assert!(matches!(replay.shot(5), Some(GameEvent::ShotFired { player: PlayerType::Player1, hit: true, sunk: Some(_), .. })));
+        assert_eq!(replay.shot(0), None);
+    }
+
+    #[test]
+    fn to_string_and_back() {
+        let replay = Replay::new(&finished_game()).unwrap();
+        let serialized = replay.to_string();
+        assert!(serialized.starts_with("battleship-replay|1\nclass|Dinghy|2\nevent|created|None|"));
+        assert_eq!(Replay::from_str(&serialized).unwrap(), replay);
+    }
+
+    #[test]
+    fn broken_replays_are_rejected() {
+        let serialized = Replay::new(&finished_game()).unwrap().to_string();
+        assert!(Replay::from_str("").is_err());
+        assert!(Replay::from_str(&serialized.replace("class|Dinghy|2\n", "")).is_err());
+        let missing_shot: Vec<&str> = serialized.lines().filter(|line| !line.contains("fired|Player 2|1,1")).collect();
+        assert!(matches!(Replay::from_str(&missing_shot.join("\n")), Err(GameError::NotYourTurn { .. })));
+        let flipped_hit = serialized.replace("fired|Player 2|1,1|false|None", "fired|Player 2|1,1|true|None");
+        assert_ne!(flipped_hit, serialized);
+        assert!(matches!(Replay::from_str(&flipped_hit), Err(GameError::InvalidEvent { .. })));
+        // the kind of shot has to match the shots per turn rule
+        let salvo_in_single = serialized.replace("fired|Player 2|1,1|false|None", "salvo|Player 2|1,1|false|");
+        assert_ne!(salvo_in_single, serialized);
+        assert!(matches!(Replay::from_str(&salvo_in_single), Err(GameError::InvalidEvent { .. })));
+        let single_in_salvo = serialized.replace("|3x3|Dinghy:1\n", "|3x3|Dinghy:1|shots=2\n");
+        assert_ne!(single_in_salvo, serialized);
+        assert!(matches!(Replay::from_str(&single_in_salvo), Err(GameError::InvalidEvent { .. })));
+    }
+}
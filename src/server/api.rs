@@ -0,0 +1,222 @@
+use serde::{Deserialize, Serialize};
+use crate::config::FirstShooter;
+use crate::error::GameError;
+use crate::game::{Direction, EnemyBoardCell, GameResult, GameState, OwnBoardCell};
+use crate::location::Location;
+use crate::player::{PlayerTurn, PlayerType};
+use crate::ship::Ship;
+
+/// Body of `POST /games`, every field is optional.
+#[derive(Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct NewGame {
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    /// A preset name or a fleet string such as `"Destroyer:2;Cruiser:1"`.
+    pub fleet: Option<String>,
+    pub first_shooter: Option<FirstShooter>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct GameCreated {
+    pub id: String,
+}
+
+/// Body of `POST /games/{id}/join`, without a player the first free seat is taken.
+#[derive(Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct Join {
+    pub player: Option<PlayerType>,
+}
+
+/// The seat a player joined, `token` is sent back as `Authorization: Bearer <token>`.
+#[derive(Serialize, Debug)]
+pub struct Joined {
+    pub player: PlayerType,
+    pub token: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Place {
+    pub class: String,
+    pub location: Location,
+    pub direction: Direction,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Remove {
+    pub class: String,
+    pub location: Location,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Fire {
+    pub location: Location,
+}
+
+/// An action sent over the WebSocket protocol, tagged by `type`.
+#[derive(Deserialize, Debug)]
+#[serde(tag = "type")]
+pub enum Command {
+    Place(Place),
+    Remove(Remove),
+    Fire(Fire),
+}
+
+/// The game as one player sees it. The opponent's ships are only included once sunk.
+#[derive(Serialize, Clone, Debug)]
+pub struct PlayerView {
+    pub id: String,
+    pub player: PlayerType,
+    pub result: GameResult,
+    pub turn: PlayerTurn,
+    pub width: u32,
+    pub height: u32,
+    pub ships: Vec<Ship>,
+    pub enemy_ships: Vec<Ship>,
+    pub own_board: Vec<Vec<OwnBoardCell>>,
+    pub enemy_board: Vec<Vec<EnemyBoardCell>>,
+    pub messages: Vec<String>,
+}
+
+impl PlayerView {
+    pub fn new(id: &str, state: &GameState, player: PlayerType) -> Self {
+        PlayerView {
+            id: String::from(id),
+            player,
+            result: state.result(),
+            turn: state.turn().clone(),
+            width: state.width(),
+            height: state.height(),
+            ships: state.ships(&player).clone(),
+            enemy_ships: state.ships(&PlayerType::other(&player)).iter().filter(|ship| ship.sunk()).cloned().collect(),
+            own_board: state.as_own_board(&player),
+            enemy_board: state.as_enemy_board(&player),
+            messages: state.messages().clone(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Serialize, Debug)]
+pub struct CellChange<T> {
+    pub row: u32,
+    pub col: u32,
+    pub cell: T,
+}
+
+/// The difference between two views of one player, only the cells that changed are listed.
+#[derive(Serialize, Debug)]
+pub struct ViewDiff {
+    pub result: GameResult,
+    pub turn: PlayerTurn,
+    pub own_board: Vec<CellChange<OwnBoardCell>>,
+    pub enemy_board: Vec<CellChange<EnemyBoardCell>>,
+}
+
+impl ViewDiff {
+    /// `None` when nothing changed.
+    pub fn between(before: &PlayerView, after: &PlayerView) -> Option<ViewDiff> {
+        let diff = ViewDiff {
+            result: after.result,
+            turn: after.turn.clone(),
+            own_board: changes(&before.own_board, &after.own_board),
+            enemy_board: changes(&before.enemy_board, &after.enemy_board),
+        };
+        let unchanged = diff.own_board.is_empty() && diff.enemy_board.is_empty()
+            && before.result == after.result && before.turn == after.turn;
+        if unchanged { None } else { Some(diff) }
+    }
+}
+
+fn changes<T: Copy + PartialEq>(before: &[Vec<T>], after: &[Vec<T>]) -> Vec<CellChange<T>> {
+    let mut changes = Vec::new();
+    for (row, (before, after)) in before.iter().zip(after).enumerate() {
+        for (col, (before, after)) in before.iter().zip(after).enumerate() {
+            if before != after {
+                changes.push(CellChange { row: row as u32, col: col as u32, cell: *after });
+            }
+        }
+    }
+    changes
+}
+
+/// An error response: `{"error": "AlreadyFired", "message": "..."}`.
+#[derive(Serialize, Debug)]
+pub struct ApiError {
+    #[serde(skip)]
+    pub status: u16,
+    pub error: String,
+    pub message: String,
+}
+
+impl ApiError {
+    fn new(status: u16, error: &str, message: String) -> Self {
+        ApiError { status, error: String::from(error), message }
+    }
+
+    pub fn bad_request(message: String) -> Self {
+        ApiError::new(400, "BadRequest", message)
+    }
+
+    pub fn unauthorized(message: String) -> Self {
+        ApiError::new(401, "Unauthorized", message)
+    }
+
+    pub fn forbidden(message: String) -> Self {
+        ApiError::new(403, "Forbidden", message)
+    }
+
+    pub fn not_found(message: String) -> Self {
+        ApiError::new(404, "NotFound", message)
+    }
+
+    pub fn conflict(message: String) -> Self {
+        ApiError::new(409, "Conflict", message)
+    }
+}
+
+impl From<GameError> for ApiError {
+    fn from(error: GameError) -> Self {
+        let (status, name) = match &error {
+            GameError::NotYourTurn { .. } => (409, "NotYourTurn"),
+            GameError::WrongPhase { .. } => (409, "WrongPhase"),
+            GameError::OutOfBounds { .. } => (422, "OutOfBounds"),
+            GameError::NoRoom { .. } => (422, "NoRoom"),
+            GameError::Overlap { .. } => (422, "Overlap"),
+            GameError::AlreadyFired { .. } => (422, "AlreadyFired"),
+            GameError::NoShipsOfClassLeft { .. } => (422, "NoShipsOfClassLeft"),
+            GameError::ShipNotFound { .. } => (422, "ShipNotFound"),
+            GameError::NoLayout { .. } => (422, "NoLayout"),
+            GameError::InvalidEvent { .. } => (422, "InvalidEvent"),
+            GameError::WrongSalvoSize { .. } => (422, "WrongSalvoSize"),
+            GameError::Touching { .. } => (422, "Touching"),
+            GameError::InvalidShipClass { .. } => (400, "InvalidShipClass"),
+            GameError::InvalidConfig { .. } => (400, "InvalidConfig"),
+            GameError::ParseError { .. } => (400, "ParseError"),
+        };
+        ApiError::new(status, name, error.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::{Direction, EnemyBoardCell, GameState, OwnBoardCell};
+    use crate::location::Location;
+    use crate::player::{PlayerTurn, PlayerType};
+    use crate::server::api::{PlayerView, ViewDiff};
+    use crate::ship::ShipType;
+
+    #[test]
+    fn diff_lists_changed_cells() {
+        let before = GameState::new();
+        let view = PlayerView::new("id", &before, PlayerType::Player1);
+        assert!(ViewDiff::between(&view, &view).is_none());
+
+        let after = before.place(PlayerType::Player1, ShipType::Destroyer, Location { row: 3, col: 4 }, Direction::Down).unwrap();
+        let diff = ViewDiff::between(&view, &PlayerView::new("id", &after, PlayerType::Player1)).unwrap();
+        let cells: Vec<(u32, u32, OwnBoardCell)> = diff.own_board.iter().map(|change| (change.row, change.col, change.cell)).collect();
+        assert_eq!(cells, vec![(3, 4, OwnBoardCell::Ship), (4, 4, OwnBoardCell::Ship)]);
+        assert_eq!(diff.turn, PlayerTurn::Either);
+        assert!(diff.enemy_board.iter().all(|change| change.cell != EnemyBoardCell::Empty));
+    }
+}
@@ -0,0 +1,138 @@
+use serde::Serialize;
+use crate::game::{Direction, GameResult, GameState};
+use crate::location::Location;
+use crate::player::{PlayerTurn, PlayerType};
+
+/// Something that happened in a hosted game, streamed to the players as it happens.
+#[derive(Serialize, Clone, Eq, PartialEq, Debug)]
+#[serde(tag = "type")]
+pub enum Event {
+    /// `location` and `direction` are left out when the ship belongs to the opponent.
+    ShipPlaced {
+        player: PlayerType,
+        class: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        location: Option<Location>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        direction: Option<Direction>,
+    },
+    ShipRemoved {
+        player: PlayerType,
+        class: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        location: Option<Location>,
+    },
+    ShotFired {
+        player: PlayerType,
+        location: Location,
+        hit: bool,
+        /// The class of the ship the shot sunk, if it sunk one.
+        sunk: Option<String>,
+    },
+    TurnChanged { turn: PlayerTurn },
+    GameOver { winner: PlayerType },
+    /// A new entry of the game's messages.
+    Message { text: String },
+}
+
+impl Event {
+    pub fn placed(player: PlayerType, class: String, location: Location, direction: Direction) -> Self {
+        Event::ShipPlaced { player, class, location: Some(location), direction: Some(direction) }
+    }
+
+    pub fn removed(player: PlayerType, class: String, location: Location) -> Self {
+        Event::ShipRemoved { player, class, location: Some(location) }
+    }
+
+    /// The shot `player` fired at `location` to get to `state`.
+    pub fn fired(state: &GameState, player: PlayerType, location: Location) -> Self {
+        let hit = state.shots(&player).last().map(|shot| shot.hit).unwrap_or(false);
+        let sunk = state.ships(&PlayerType::other(&player)).iter()
+            .find(|ship| ship.sunk() && ship.locations.contains(&location))
+            .map(|ship| ship.class.to_string());
+        Event::ShotFired { player, location, hit, sunk }
+    }
+
+    /// The event as `viewer` is allowed to see it, without where the opponent's ships are.
+    pub fn visible_to(&self, viewer: PlayerType) -> Event {
+        match self {
+            Event::ShipPlaced { player, class, .. } if *player != viewer =>
+                Event::ShipPlaced { player: *player, class: class.clone(), location: None, direction: None },
+            Event::ShipRemoved { player, class, .. } if *player != viewer =>
+                Event::ShipRemoved { player: *player, class: class.clone(), location: None },
+            event => event.clone(),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Event::ShipPlaced { .. } => "ShipPlaced",
+            Event::ShipRemoved { .. } => "ShipRemoved",
+            Event::ShotFired { .. } => "ShotFired",
+            Event::TurnChanged { .. } => "TurnChanged",
+            Event::GameOver { .. } => "GameOver",
+            Event::Message { .. } => "Message",
+        }
+    }
+}
+
+/// The events for an action that took the game from `before` to `after`: the action itself,
+/// then any change of turn, the end of the game and the new messages.
+pub fn after_action(before: &GameState, after: &GameState, action: Event) -> Vec<Event> {
+    let mut events = vec![action];
+    if before.turn() != after.turn() {
+        events.push(Event::TurnChanged { turn: after.turn().clone() });
+    }
+    if before.result() != after.result() {
+        match after.result() {
+            GameResult::Player1Win => events.push(Event::GameOver { winner: PlayerType::Player1 }),
+            GameResult::Player2Win => events.push(Event::GameOver { winner: PlayerType::Player2 }),
+            _ => {}
+        }
+    }
+    for text in after.messages().iter().skip(before.messages().len()) {
+        events.push(Event::Message { text: text.clone() });
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::GameConfig;
+    use crate::fleet::FleetSpec;
+    use crate::game::{Direction, GameState};
+    use crate::location::Location;
+    use crate::player::{PlayerTurn, PlayerType};
+    use crate::server::events::{after_action, Event};
+    use crate::ship::ShipType;
+
+    #[test]
+    fn events_for_a_winning_shot() {
+        let config = GameConfig { fleet: FleetSpec::new(vec![(ShipType::TorpedoBoat, 1)]), ..GameConfig::with_grid(2, 2) };
+        let state = GameState::with_config(config).unwrap();
+        let location = Location { row: 0, col: 0 };
+        let state = state.place(PlayerType::Player1, ShipType::TorpedoBoat, location, Direction::Right).unwrap();
+        let before = state.place(PlayerType::Player2, ShipType::TorpedoBoat, location, Direction::Right).unwrap();
+        let after = before.fire(PlayerType::Player1, &location).unwrap();
+
+        let shot = Event::fired(&after, PlayerType::Player1, location);
+        assert_eq!(shot, Event::ShotFired { player: PlayerType::Player1, location, hit: true, sunk: Some(String::from("Torpedo Boat")) });
+        assert_eq!(after_action(&before, &after, shot.clone()), vec![
+            shot,
+            Event::TurnChanged { turn: PlayerTurn::Neither },
+            Event::GameOver { winner: PlayerType::Player1 },
+            Event::Message { text: String::from("Player 1 sunk Player 2's Torpedo Boat!") },
+            Event::Message { text: String::from("Game over. Player 1 wins!") },
+        ]);
+    }
+
+    #[test]
+    fn opponent_placements_are_hidden() {
+        let placed = Event::placed(PlayerType::Player1, String::from("Carrier"), Location { row: 1, col: 2 }, Direction::Up);
+        assert_eq!(placed.visible_to(PlayerType::Player1), placed);
+        assert_eq!(
+            placed.visible_to(PlayerType::Player2),
+            Event::ShipPlaced { player: PlayerType::Player1, class: String::from("Carrier"), location: None, direction: None }
+        );
+    }
+}
@@ -0,0 +1,232 @@
+//! An HTTP/JSON server hosting games in memory, see docs/api.md for the endpoints.
+
+use std::io::{self, Write};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tiny_http::{Header, Method, Request, Response};
+use crate::config::GameConfig;
+use crate::fleet::FleetSpec;
+use crate::game::GameState;
+use crate::player::PlayerType;
+use crate::server::api::{ApiError, Command, Fire, GameCreated, Join, Joined, NewGame, Place, PlayerView, Remove};
+use crate::server::events::Event;
+use crate::server::store::{GameStore, HostedGame};
+
+mod api;
+mod events;
+mod store;
+mod websocket;
+
+// how long an event stream stays quiet before a comment is sent to check the client is still there
+const KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+pub struct Server {
+    http: Arc<tiny_http::Server>,
+    store: Arc<GameStore>,
+}
+
+// a JSON response body and its status code
+type Reply = (u16, String);
+
+impl Server {
+    /// Listens on `addr`, use port 0 to let the OS pick a free port.
+    pub fn bind(addr: &str) -> io::Result<Server> {
+        let http = tiny_http::Server::http(addr).map_err(|e| io::Error::new(io::ErrorKind::AddrNotAvailable, e))?;
+        Ok(Server { http: Arc::new(http), store: Arc::new(GameStore::default()) })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.http.server_addr().to_ip().expect("server listens on a TCP address")
+    }
+
+    /// Accepts WebSocket connections on `addr` in the background, sharing this server's games.
+    pub fn listen_websockets(&self, addr: &str) -> io::Result<SocketAddr> {
+        websocket::listen(addr, self.store.clone())
+    }
+
+    /// Serves requests until the process exits, each request on its own thread.
+    pub fn run(&self) {
+        for request in self.http.incoming_requests() {
+            let store = self.store.clone();
+            thread::spawn(move || handle(&store, request));
+        }
+    }
+}
+
+fn handle(store: &GameStore, mut request: Request) {
+    let url = request.url().to_string();
+    let method = request.method().clone();
+    let token = request_token(&request, &url);
+    let path = url.split('?').next().unwrap_or_default();
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+
+    let reply = match segments.as_slice() {
+        ["games", id, "players", player, "events"] if method == Method::Get => {
+            let authorized = player_from_path(player)
+                .and_then(|player| store.with_game(id, |game| game.authorize(player, token.as_deref())).map(|_| player));
+            match authorized {
+                Ok(player) => return stream_events(store, request, id, player),
+                Err(error) => Err(error),
+            }
+        }
+        _ => {
+            let mut body = String::new();
+            match request.as_reader().read_to_string(&mut body) {
+                Ok(_) => route(store, &method, path, &segments, token.as_deref(), &body),
+                Err(error) => Err(ApiError::bad_request(error.to_string())),
+            }
+        }
+    };
+    let (status, json) = reply.unwrap_or_else(|error| (error.status, to_json(&error)));
+    let content_type = Header::from_bytes("Content-Type", "application/json").unwrap();
+    let _ = request.respond(Response::from_string(json).with_status_code(status).with_header(content_type));
+}
+
+fn header<'r>(request: &'r Request, name: &'static str) -> Option<&'r str> {
+    request.headers().iter().find(|header| header.field.equiv(name)).map(|header| header.value.as_str())
+}
+
+// the bearer token, or a `token` query parameter for clients such as EventSource that can't set headers
+fn request_token(request: &Request, url: &str) -> Option<String> {
+    if let Some(token) = header(request, "Authorization").and_then(|value| value.strip_prefix("Bearer ")) {
+        return Some(token.trim().to_string());
+    }
+    query_token(url.split_once('?')?.1)
+}
+
+fn query_token(query: &str) -> Option<String> {
+    query.split('&').find_map(|pair| pair.strip_prefix("token=")).map(String::from)
+}
+
+fn route(store: &GameStore, method: &Method, path: &str, segments: &[&str], token: Option<&str>, body: &str) -> Result<Reply, ApiError> {
+    match (method, segments) {
+        (Method::Post, ["games"]) => create(store, body),
+        (Method::Post, ["games", id, "join"]) => join(store, id, body),
+        (Method::Get, ["games", id, "players", player]) => {
+            let player = player_from_path(player)?;
+            store.with_game(id, |game| {
+                game.authorize(player, token)?;
+                Ok((200, to_json(&PlayerView::new(id, &game.state, player))))
+            })
+        }
+        (Method::Post, ["games", id, "players", player, action]) => act(store, id, player_from_path(player)?, token, action, body),
+        _ => Err(ApiError::not_found(format!("No route for {} {}", method, path))),
+    }
+}
+
+// sends the game's events to the player as server-sent events, starting after `Last-Event-ID`
+// when the client is reconnecting, until the game is over or the client goes away
+fn stream_events(store: &GameStore, request: Request, id: &str, player: PlayerType) {
+    let mut next = header(&request, "Last-Event-ID").and_then(|id| id.trim().parse::<usize>().ok()).map_or(0, |id| id + 1);
+    let mut writer = request.into_writer();
+    // the body is chunked by hand, so each batch of events goes out as soon as it happens
+    let head = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nTransfer-Encoding: chunked\r\n\r\n";
+    if writer.write_all(head.as_bytes()).and_then(|_| writer.flush()).is_err() {
+        return;
+    }
+    while let Ok((after, events)) = store.events(id, next, KEEP_ALIVE) {
+        let mut chunk = String::new();
+        if events.is_empty() {
+            chunk.push_str(": keep-alive\n\n");
+        }
+        for (index, event) in events.iter().enumerate() {
+            let event = event.visible_to(player);
+            chunk.push_str(&format!("id: {}\nevent: {}\ndata: {}\n\n", next + index, event.name(), to_json(&event)));
+        }
+        let sent = write!(writer, "{:X}\r\n{}\r\n", chunk.len(), chunk).and_then(|_| writer.flush());
+        if sent.is_err() {
+            return;
+        }
+        if events.iter().any(|event| matches!(event, Event::GameOver { .. })) {
+            break;
+        }
+        next = after;
+    }
+    let _ = writer.write_all(b"0\r\n\r\n").and_then(|_| writer.flush());
+}
+
+fn create(store: &GameStore, body: &str) -> Result<Reply, ApiError> {
+    let request: NewGame = from_json(body)?;
+    let mut config = GameConfig::default();
+    config.width = request.width.unwrap_or(config.width);
+    config.height = request.height.unwrap_or(config.height);
+    if let Some(first_shooter) = request.first_shooter {
+        config.first_shooter = first_shooter;
+    }
+    if let Some(fleet) = request.fleet {
+        config.fleet = match FleetSpec::preset(&fleet) {
+            Some(fleet) => fleet,
+            None => FleetSpec::parse(&fleet, &config.registry)?,
+        };
+    }
+    let id = store.create(GameState::with_config(config)?);
+    Ok((201, to_json(&GameCreated { id })))
+}
+
+fn join(store: &GameStore, id: &str, body: &str) -> Result<Reply, ApiError> {
+    let request: Join = from_json(body)?;
+    let (player, token) = store.with_game(id, |game| game.join(request.player))?;
+    Ok((200, to_json(&Joined { player, token })))
+}
+
+// place, remove or fire for a player, replying with the game as they now see it
+fn act(store: &GameStore, id: &str, player: PlayerType, token: Option<&str>, action: &str, body: &str) -> Result<Reply, ApiError> {
+    let command = match action {
+        "place" => Command::Place(from_json(body)?),
+        "remove" => Command::Remove(from_json(body)?),
+        "fire" => Command::Fire(from_json(body)?),
+        _ => return Err(ApiError::not_found(format!("Unknown action {}", action))),
+    };
+    store.with_game(id, |game| {
+        game.authorize(player, token)?;
+        apply(game, player, command)?;
+        Ok((200, to_json(&PlayerView::new(id, &game.state, player))))
+    })
+}
+
+// runs the command against the game and records the events it caused
+fn apply(game: &mut HostedGame, player: PlayerType, command: Command) -> Result<(), ApiError> {
+    let registry = &game.state.config().registry;
+    let (next, event) = match command {
+        Command::Place(Place { class, location, direction }) => {
+            let class = registry.parse(&class)?;
+            let next = game.state.place(player, class.clone(), location, direction)?;
+            (next, Event::placed(player, class.to_string(), location, direction))
+        }
+        Command::Remove(Remove { class, location }) => {
+            let class = registry.parse(&class)?;
+            let next = game.state.remove(player, class.clone(), location)?;
+            (next, Event::removed(player, class.to_string(), location))
+        }
+        Command::Fire(Fire { location }) => {
+            let next = game.state.fire(player, &location)?;
+            let event = Event::fired(&next, player, location);
+            (next, event)
+        }
+    };
+    game.events.extend(events::after_action(&game.state, &next, event));
+    game.state = next;
+    Ok(())
+}
+
+fn player_from_path(player: &str) -> Result<PlayerType, ApiError> {
+    match player {
+        "1" => Ok(PlayerType::Player1),
+        "2" => Ok(PlayerType::Player2),
+        _ => Err(ApiError::not_found(format!("No player {}, expected 1 or 2", player))),
+    }
+}
+
+// an empty body reads as `{}` so requests with only optional fields can leave it out
+fn from_json<T: DeserializeOwned>(body: &str) -> Result<T, ApiError> {
+    let body = if body.trim().is_empty() { "{}" } else { body };
+    serde_json::from_str(body).map_err(|e| ApiError::bad_request(format!("Invalid request body: {}", e)))
+}
+
+fn to_json<T: Serialize>(value: &T) -> String {
+    serde_json::to_string(value).expect("responses always serialize")
+}
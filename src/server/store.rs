@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+use std::sync::{Condvar, Mutex};
+use std::time::Duration;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use crate::game::GameState;
+use crate::player::PlayerType;
+use crate::server::api::ApiError;
+use crate::server::events::Event;
+
+const ID_LENGTH: usize = 12;
+const TOKEN_LENGTH: usize = 32;
+
+fn random_string(length: usize) -> String {
+    rand::thread_rng().sample_iter(&Alphanumeric).take(length).map(char::from).collect()
+}
+
+// compares every byte so the time taken doesn't reveal how much of a guessed token was right
+fn tokens_match(expected: &str, actual: &str) -> bool {
+    expected.len() == actual.len() && expected.bytes().zip(actual.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// A game hosted by the server, the secret token of each seat that has been taken and
+/// everything that has happened in the game so far.
+pub struct HostedGame {
+    pub state: GameState,
+    tokens: [Option<String>; 2],
+    pub events: Vec<Event>,
+}
+
+impl HostedGame {
+    fn seat(player: PlayerType) -> usize {
+        match player {
+            PlayerType::Player1 => 0,
+            PlayerType::Player2 => 1,
+        }
+    }
+
+    pub fn joined(&self, player: PlayerType) -> bool {
+        self.tokens[HostedGame::seat(player)].is_some()
+    }
+
+    /// Takes the requested seat, or the first free one when `player` is `None`, and returns
+    /// the token the player has to present to act in that seat.
+    pub fn join(&mut self, player: Option<PlayerType>) -> Result<(PlayerType, String), ApiError> {
+        let player = match player {
+            Some(player) if self.joined(player) => return Err(ApiError::conflict(format!("{} has already joined", player))),
+            Some(player) => player,
+            None => [PlayerType::Player1, PlayerType::Player2].into_iter()
+                .find(|player| !self.joined(*player))
+                .ok_or_else(|| ApiError::conflict(String::from("Both players have already joined")))?,
+        };
+        let token = random_string(TOKEN_LENGTH);
+        self.tokens[HostedGame::seat(player)] = Some(token.clone());
+        Ok((player, token))
+    }
+
+    /// Checks that `token` belongs to the player's seat.
+    pub fn authorize(&self, player: PlayerType, token: Option<&str>) -> Result<(), ApiError> {
+        match (&self.tokens[HostedGame::seat(player)], token) {
+            (None, _) => Err(ApiError::forbidden(format!("{} has not joined this game", player))),
+            (Some(_), None) => Err(ApiError::unauthorized(String::from("Missing bearer token"))),
+            (Some(expected), Some(token)) if tokens_match(expected, token) => Ok(()),
+            (Some(_), Some(_)) => Err(ApiError::forbidden(format!("Token does not belong to {}", player))),
+        }
+    }
+}
+
+/// Games in memory, keyed by a random id. Games are lost when the server stops.
+#[derive(Default)]
+pub struct GameStore {
+    games: Mutex<HashMap<String, HostedGame>>,
+    // notified whenever a game changes, wakes up the event streams
+    changed: Condvar,
+}
+
+impl GameStore {
+    pub fn create(&self, state: GameState) -> String {
+        let mut games = self.games.lock().unwrap();
+        let id = loop {
+            let id = random_string(ID_LENGTH);
+            if !games.contains_key(&id) {
+                break id;
+            }
+        };
+        games.insert(id.clone(), HostedGame { state, tokens: [None, None], events: Vec::new() });
+        id
+    }
+
+    /// Runs `action` against the game with the lock held, so actions on one game can't interleave.
+    pub fn with_game<T>(&self, id: &str, action: impl FnOnce(&mut HostedGame) -> Result<T, ApiError>) -> Result<T, ApiError> {
+        let mut games = self.games.lock().unwrap();
+        let result = match games.get_mut(id) {
+            Some(game) => action(game),
+            None => Err(ApiError::not_found(format!("No game with id {}", id))),
+        };
+        self.changed.notify_all();
+        result
+    }
+
+    /// The game's events from index `from` on, waiting up to `timeout` for one to happen if
+    /// there are none yet. Returns the index of the next event along with them.
+    pub fn events(&self, id: &str, from: usize, timeout: Duration) -> Result<(usize, Vec<Event>), ApiError> {
+        let games = self.games.lock().unwrap();
+        let (games, _) = self.changed.wait_timeout_while(games, timeout, |games| {
+            games.get(id).map(|game| game.events.len() <= from).unwrap_or(false)
+        }).unwrap();
+        match games.get(id) {
+            Some(game) => Ok((game.events.len().max(from), game.events.iter().skip(from).cloned().collect())),
+            None => Err(ApiError::not_found(format!("No game with id {}", id))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::GameState;
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+    use crate::player::{PlayerTurn, PlayerType};
+    use crate::server::events::Event;
+    use crate::server::store::GameStore;
+
+    #[test]
+    fn players_join_free_seats() {
+        let store = GameStore::default();
+        let id = store.create(GameState::new());
+        assert_ne!(store.create(GameState::new()), id);
+
+        store.with_game(&id, |game| {
+            assert_eq!(game.join(Some(PlayerType::Player2)).unwrap().0, PlayerType::Player2);
+            assert!(game.join(Some(PlayerType::Player2)).is_err());
+            assert_eq!(game.join(None).unwrap().0, PlayerType::Player1);
+            assert!(game.join(None).is_err());
+            Ok(())
+        }).unwrap();
+        assert_eq!(store.with_game("missing", |_| Ok(())).unwrap_err().status, 404);
+    }
+
+    #[test]
+    fn tokens_authorize_their_seat_only() {
+        let store = GameStore::default();
+        let id = store.create(GameState::new());
+        store.with_game(&id, |game| {
+            assert_eq!(game.authorize(PlayerType::Player1, None).unwrap_err().status, 403);
+            let (_, token1) = game.join(Some(PlayerType::Player1))?;
+            let (_, token2) = game.join(Some(PlayerType::Player2))?;
+            assert_ne!(token1, token2);
+            assert!(game.authorize(PlayerType::Player1, Some(&token1)).is_ok());
+            assert_eq!(game.authorize(PlayerType::Player1, Some(&token2)).unwrap_err().status, 403);
+            assert_eq!(game.authorize(PlayerType::Player1, Some("")).unwrap_err().status, 403);
+            assert_eq!(game.authorize(PlayerType::Player2, None).unwrap_err().status, 401);
+            Ok(())
+        }).unwrap();
+    }
+
+    #[test]
+    fn waiting_for_events() {
+        let store = Arc::new(GameStore::default());
+        let id = store.create(GameState::new());
+        assert_eq!(store.events(&id, 0, Duration::from_millis(10)).unwrap(), (0, Vec::new()));
+
+        let event = Event::TurnChanged { turn: PlayerTurn::Player2 };
+        let writer = {
+            let (store, id, event) = (store.clone(), id.clone(), event.clone());
+            thread::spawn(move || store.with_game(&id, |game| {
+                game.events.push(event);
+                Ok(())
+            }))
+        };
+        assert_eq!(store.events(&id, 0, Duration::from_secs(10)).unwrap(), (1, vec![event]));
+        writer.join().unwrap().unwrap();
+        assert_eq!(store.events(&id, 1, Duration::from_millis(10)).unwrap(), (1, Vec::new()));
+        assert_eq!(store.events("missing", 0, Duration::from_millis(10)).unwrap_err().status, 404);
+    }
+}
@@ -0,0 +1,148 @@
+//! The WebSocket protocol, see docs/websocket.md.
+
+// tungstenite's error and handshake response types are large, and this is where they come from
+#![allow(clippy::result_large_err)]
+
+use std::io;
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use serde::Serialize;
+use tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tungstenite::http::{HeaderValue, StatusCode};
+use tungstenite::{Message, WebSocket};
+use crate::player::PlayerType;
+use crate::server::api::{ApiError, Command, PlayerView, ViewDiff};
+use crate::server::events::Event;
+use crate::server::store::GameStore;
+use crate::server::{apply, player_from_path, query_token, to_json};
+
+/// Bumped whenever a message changes in a way old clients can't read.
+pub const PROTOCOL_VERSION: u32 = 1;
+const SUBPROTOCOL: &str = "battleship.v1";
+// how long a connection waits on the client before checking the game for new events
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Serialize, Debug)]
+#[serde(tag = "type")]
+enum ServerMessage {
+    Hello { version: u32, view: PlayerView },
+    Event { id: usize, event: Event },
+    Diff(ViewDiff),
+    Error(ApiError),
+}
+
+/// Accepts WebSocket connections on `addr`, each on its own thread.
+pub fn listen(addr: &str, store: Arc<GameStore>) -> io::Result<SocketAddr> {
+    let listener = TcpListener::bind(addr)?;
+    let addr = listener.local_addr()?;
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let store = store.clone();
+            thread::spawn(move || connection(&store, stream));
+        }
+    });
+    Ok(addr)
+}
+
+fn connection(store: &GameStore, stream: TcpStream) {
+    let mut seat = None;
+    let handshake = |request: &Request, mut response: Response| {
+        seat = Some(authorize(store, request).map_err(error_response)?);
+        if let Some(protocols) = request.headers().get("Sec-WebSocket-Protocol") {
+            let offered = protocols.to_str().unwrap_or_default();
+            if !offered.split(',').any(|protocol| protocol.trim() == SUBPROTOCOL) {
+                return Err(error_response(ApiError::bad_request(format!("Unsupported protocol {}, expected {}", offered, SUBPROTOCOL))));
+            }
+            response.headers_mut().insert("Sec-WebSocket-Protocol", HeaderValue::from_static(SUBPROTOCOL));
+        }
+        Ok(response)
+    };
+    let mut socket = match tungstenite::accept_hdr(stream, handshake) {
+        Ok(socket) => socket,
+        Err(_) => return,
+    };
+    if let Some((id, player)) = seat {
+        let _ = play(store, &mut socket, &id, player);
+    }
+}
+
+// the game id and seat in the request path, checked against the token
+fn authorize(store: &GameStore, request: &Request) -> Result<(String, PlayerType), ApiError> {
+    let path = request.uri().path();
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let (id, player) = match segments.as_slice() {
+        ["games", id, "players", player] => (id.to_string(), player_from_path(player)?),
+        _ => return Err(ApiError::not_found(format!("No game at {}", path))),
+    };
+    let bearer = request.headers().get("Authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|token| token.trim().to_string());
+    let token = bearer.or_else(|| query_token(request.uri().query().unwrap_or_default()));
+    store.with_game(&id, |game| game.authorize(player, token.as_deref()))?;
+    Ok((id, player))
+}
+
+fn error_response(error: ApiError) -> ErrorResponse {
+    let mut response = ErrorResponse::new(Some(to_json(&error)));
+    *response.status_mut() = StatusCode::from_u16(error.status).unwrap_or(StatusCode::BAD_REQUEST);
+    response
+}
+
+// runs commands from the client and pushes everything that happens in the game back, until
+// the client disconnects
+fn play(store: &GameStore, socket: &mut WebSocket<TcpStream>, id: &str, player: PlayerType) -> tungstenite::Result<()> {
+    socket.get_ref().set_read_timeout(Some(POLL_INTERVAL))?;
+    let (mut view, mut next) = match snapshot(store, id, player) {
+        Ok(snapshot) => snapshot,
+        Err(error) => return send(socket, ServerMessage::Error(error)),
+    };
+    send(socket, ServerMessage::Hello { version: PROTOCOL_VERSION, view: view.clone() })?;
+
+    loop {
+        match socket.read() {
+            Ok(Message::Text(text)) => {
+                let applied = serde_json::from_str::<Command>(&text)
+                    .map_err(|e| ApiError::bad_request(format!("Invalid command: {}", e)))
+                    .and_then(|command| store.with_game(id, |game| apply(game, player, command)));
+                if let Err(error) = applied {
+                    send(socket, ServerMessage::Error(error))?;
+                }
+            }
+            Ok(_) => {}
+            Err(tungstenite::Error::Io(error)) if matches!(error.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => {}
+            Err(error) => return Err(error),
+        }
+
+        let (after, events) = match store.events(id, next, Duration::ZERO) {
+            Ok(events) => events,
+            Err(error) => return send(socket, ServerMessage::Error(error)),
+        };
+        if events.is_empty() {
+            continue;
+        }
+        for (index, event) in events.iter().enumerate() {
+            send(socket, ServerMessage::Event { id: next + index, event: event.visible_to(player) })?;
+        }
+        let (latest, _) = match snapshot(store, id, player) {
+            Ok(snapshot) => snapshot,
+            Err(error) => return send(socket, ServerMessage::Error(error)),
+        };
+        if let Some(diff) = ViewDiff::between(&view, &latest) {
+            send(socket, ServerMessage::Diff(diff))?;
+        }
+        view = latest;
+        next = after;
+    }
+}
+
+// the player's view along with how many events it includes
+fn snapshot(store: &GameStore, id: &str, player: PlayerType) -> Result<(PlayerView, usize), ApiError> {
+    store.with_game(id, |game| Ok((PlayerView::new(id, &game.state, player), game.events.len())))
+}
+
+fn send(socket: &mut WebSocket<TcpStream>, message: ServerMessage) -> tungstenite::Result<()> {
+    socket.send(Message::Text(to_json(&message)))
+}
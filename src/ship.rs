@@ -1,61 +1,128 @@
-use std::str::FromStr;
 use std::fmt::Display;
+use std::str::FromStr;
+use crate::error::GameError;
+use crate::game::Direction;
 use crate::location;
 use crate::location::Location;
+use crate::registry::ShipRegistry;
+
+/// A ship class defined from data rather than built into `ShipType`.
+#[derive(Eq,PartialEq,Clone,Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ShipClass {
+    pub name: String,
+    pub length: u32,
+    /// The cells the ship covers when placed facing Right, relative to the location it
+    /// is placed at. When `None` the ship is a straight line of `length` cells.
+    pub shape: Option<Vec<Location>>,
+}
+
+impl ShipClass {
+    pub fn new(name: &str, length: u32) -> Self {
+        ShipClass { name: String::from(name), length, shape: None }
+    }
 
-#[derive(Eq,PartialEq,Copy,Clone)]
+    pub fn shaped(name: &str, shape: Vec<Location>) -> Self {
+        ShipClass { name: String::from(name), length: shape.len() as u32, shape: Some(shape) }
+    }
+
+    #[allow(clippy::inherent_to_string)]
+    pub fn to_string(&self) -> String {
+        match &self.shape {
+            Some(shape) => format!("{}|{}|{}", self.name, self.length, location::locations_to_string(shape)),
+            None => format!("{}|{}", self.name, self.length),
+        }
+    }
+}
+
+impl FromStr for ShipClass {
+    type Err = GameError;
+
+    fn from_str(input: &str) -> Result<Self, GameError> {
+        let parts = input.split('|').collect::<Vec<&str>>();
+        if parts.len() != 2 && parts.len() != 3 {
+            return Err(GameError::parse(input, format!("Expected ship class to contain 2 or 3 | separated values, found {}", parts.len())));
+        }
+        let length = match parts[1].parse::<u32>() {
+            Ok(length) => length,
+            Err(_) => return Err(GameError::parse(input, format!("Could not parse length, expected u32, found {}", parts[1]))),
+        };
+        let shape = match parts.get(2) {
+            Some(shape) => Some(location::locations_from_string(shape)?),
+            None => None,
+        };
+        Ok(ShipClass { name: String::from(parts[0]), length, shape })
+    }
+}
+
+#[derive(Eq,PartialEq,Clone,Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ShipType {
     Carrier,
     Battleship,
     Cruiser,
     Submarine,
     Destroyer,
+    TorpedoBoat,
+    Custom(ShipClass),
 }
 
 impl Display for ShipType {
     fn fmt(&self, f: &mut ::std::fmt::Formatter) -> std::result::Result<(), ::std::fmt::Error> {
-        match *self {
+        match self {
             ShipType::Carrier => {f.write_str("Carrier")}
             ShipType::Battleship => {f.write_str("Battleship")}
             ShipType::Cruiser => {f.write_str("Cruiser")}
             ShipType::Submarine => {f.write_str("Submarine")}
             ShipType::Destroyer => {f.write_str("Destroyer")}
+            ShipType::TorpedoBoat => {f.write_str("Torpedo Boat")}
+            ShipType::Custom(class) => {f.write_str(&class.name)}
         }
     }
 }
 
 impl ShipType {
     pub fn size(&self) -> u32 {
-        match *self {
+        match self {
             ShipType::Carrier => 5,
             ShipType::Battleship => 4,
             ShipType::Cruiser => 3,
             ShipType::Submarine => 2,
             ShipType::Destroyer => 2,
+            ShipType::TorpedoBoat => 1,
+            ShipType::Custom(class) => class.length,
         }
     }
-    fn from_str(input: &str) -> Result<Self, String> {
-        match input {
-            "Carrier"  => Ok(ShipType::Carrier),
-            "Battleship"  => Ok(ShipType::Battleship),
-            "Cruiser"  => Ok(ShipType::Cruiser),
-            "Submarine" => Ok(ShipType::Submarine),
-            "Destroyer" => Ok(ShipType::Destroyer),
-            _      => Err(format!("Could not convert {} to ShipType", input)),
-        }
+
+    /// The (row, col) offsets of the cells the ship covers when placed facing `direction`,
+    /// relative to the location it is placed at.
+    pub fn footprint(&self, direction: Direction) -> Vec<(i64, i64)> {
+        let cells: Vec<(i64, i64)> = match self {
+            ShipType::Custom(ShipClass { shape: Some(shape), .. }) =>
+                shape.iter().map(|l| (l.row as i64, l.col as i64)).collect(),
+            _ => (0..self.size() as i64).map(|col| (0, col)).collect(),
+        };
+        // shapes are defined facing Right, turn them clockwise to face the other ways
+        cells.into_iter().map(|(row, col)| match direction {
+            Direction::Right => (row, col),
+            Direction::Down => (col, -row),
+            Direction::Left => (-row, -col),
+            Direction::Up => (-col, row),
+        }).collect()
     }
-    fn to_string(&self) -> String {
-        return match &self {
-            ShipType::Carrier => String::from("Carrier"),
-            ShipType::Battleship => String::from("Battleship"),
-            ShipType::Cruiser => String::from("Cruiser"),
-            ShipType::Submarine => String::from("Submarine"),
-            ShipType::Destroyer => String::from("Destroyer"),
-        }
+}
+
+impl FromStr for ShipType {
+    type Err = GameError;
+
+    /// Parses one of the built in classes, use `ShipRegistry::parse` to include custom classes.
+    fn from_str(input: &str) -> Result<Self, GameError> {
+        ShipRegistry::standard().parse(input)
     }
 }
 
-#[derive(Eq, PartialEq, Clone)]
+#[derive(Eq, PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Ship {
     pub class: ShipType,
     pub locations: Vec<Location>,
@@ -64,7 +131,7 @@ pub struct Ship {
 
 impl Ship {
     pub fn new(class: ShipType) -> Self {
-        return Ship {
+        Ship {
             class,
             locations: Vec::new(),
             hits: 0,
@@ -75,66 +142,79 @@ impl Ship {
     pub fn cruiser() -> Self { Ship::new(ShipType::Cruiser) }
     pub fn submarine() -> Self { Ship::new(ShipType::Submarine) }
     pub fn destroyer() -> Self { Ship::new(ShipType::Destroyer) }
+    pub fn torpedo_boat() -> Self { Ship::new(ShipType::TorpedoBoat) }
 
     pub fn sunk(&self) -> bool {
-        return &self.hits == &self.class.size();
+        self.hits == self.class.size()
     }
 
+    #[allow(clippy::inherent_to_string)]
     pub fn to_string(&self) -> String {
-        let str = format!(
+        format!(
             "{}|{}|{}",
-            self.class.to_string(),
+            self.class,
             self.hits,
             location::locations_to_string(&self.locations),
-        );
-        return str;
+        )
     }
+}
 
-    pub fn from_str(input: &str) -> Result<Self, String> {
-        let parts = input.split("|").collect::<Vec<&str>>();
+impl Ship {
+    /// Parses a serialized ship, resolving its class through `registry`.
+    pub fn parse(input: &str, registry: &ShipRegistry) -> Result<Self, GameError> {
+        let parts = input.split('|').collect::<Vec<&str>>();
         if parts.len() != 3 {
-            return Err(format!("Expected serialized ship to contain 3 | separated values, found {}", parts.len()));
-        }
-        let class = ShipType::from_str(parts[0])?;
-        let hits_result = parts[1].parse::<u32>();
-        if hits_result.is_err() {
-            return Err(format!("Could not parse hits, expected u32, found {}", parts[1]));
+            return Err(GameError::parse(input, format!("Expected serialized ship to contain 3 | separated values, found {}", parts.len())));
         }
-        let hits = hits_result.unwrap();
-        let locations = location::locations_from_string(parts[2])?;
-        if locations.len() != class.size().try_into().unwrap() {
-            return Err(format!("Expected {} serialized locations, found {}", class.size(), locations.len()));
+        let class = registry.parse(parts[0])?;
+        let hits = match parts[1].parse::<u32>() {
+            Ok(hits) => hits,
+            Err(_) => return Err(GameError::parse(input, format!("Could not parse hits, expected u32, found {}", parts[1]))),
+        };
+        // ships that have not been placed yet have no locations
+        let locations = match parts[2] {
+            "" => Vec::new(),
+            serialized => location::locations_from_string(serialized)?,
+        };
+        if !locations.is_empty() && locations.len() != class.size() as usize {
+            return Err(GameError::parse(input, format!("Expected {} serialized locations, found {}", class.size(), locations.len())));
         }
 
         Ok(Ship{class, hits, locations})
     }
 }
 
-pub fn ships_to_string(ships: &Vec<Ship>) -> String {
-    return ships.iter().map(|l| l.to_string()).collect::<Vec<String>>().join("&");
+impl FromStr for Ship {
+    type Err = GameError;
+
+    fn from_str(input: &str) -> Result<Self, GameError> {
+        Ship::parse(input, &ShipRegistry::standard())
+    }
 }
 
-pub fn ships_from_str(serialized: &str) -> Result<Vec<Ship>,String> {
+pub fn ships_to_string(ships: &[Ship]) -> String {
+    ships.iter().map(|l| l.to_string()).collect::<Vec<String>>().join("&")
+}
+
+pub fn ships_from_str(serialized: &str, registry: &ShipRegistry) -> Result<Vec<Ship>,GameError> {
+    if serialized.is_empty() {
+        return Ok(Vec::new());
+    }
     let ships: Vec<Ship> = serialized
-        .split("&")
+        .split('&')
         .collect::<Vec<&str>>()
         .iter()
         .filter_map( | input: &&str | -> Option<Ship> {
-            let result = Ship::from_str(input);
-            return if result.is_ok() {
-                Some(result.unwrap())
-            } else {
-                None
-            }
+            Ship::parse(input, registry).ok()
         }).collect();
 
     let expected_count = serialized
-        .split(";").count();
+        .split('&').count();
 
     if expected_count != ships.len() {
-        return Err(format!("Expected to deserialize {} ships but only deserialized {}", expected_count, ships.len()));
+        return Err(GameError::parse(serialized, format!("Expected to deserialize {} ships but only deserialized {}", expected_count, ships.len())));
     }
-    return Ok(ships);
+    Ok(ships)
 }
 
 
@@ -142,7 +222,8 @@ pub fn ships_from_str(serialized: &str) -> Result<Vec<Ship>,String> {
 mod tests {
     use std::str::FromStr;
     use crate::location::Location;
-    use crate::ship::{Ship, ships_to_string, ShipType};
+    use crate::registry::ShipRegistry;
+    use crate::ship::{Ship, ships_from_str, ships_to_string, ShipType};
 
     #[test]
     fn ship_type_to_string() {
@@ -150,7 +231,8 @@ mod tests {
         assert_eq!(ShipType::Carrier.to_string(), String::from("Carrier"));
         assert_eq!(ShipType::Cruiser.to_string(), String::from("Cruiser"));
         assert_eq!(ShipType::Destroyer.to_string(), String::from("Destroyer"));
-        assert_eq!(ShipType::Submarine.to_string(), String::from("Submarine"))
+        assert_eq!(ShipType::Submarine.to_string(), String::from("Submarine"));
+        assert_eq!(ShipType::TorpedoBoat.to_string(), String::from("Torpedo Boat"))
     }
     #[test]
     fn ship_type_from_string() {
@@ -173,6 +255,8 @@ mod tests {
 
         assert!(submarine.is_ok());
         assert!(submarine.unwrap().eq(&ShipType::Submarine));
+
+        assert_eq!(ShipType::from_str("Torpedo Boat").unwrap(), ShipType::TorpedoBoat);
     }
 
     #[test]
@@ -227,4 +311,24 @@ mod tests {
         let string = ships_to_string(&ships);
         assert_eq!(string, String::from("Submarine|0|0,0;0,1&Destroyer|0|1,0;1,1"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn unplaced_ship_to_string_and_back() {
+        let ship = Ship::carrier();
+        assert_eq!(ship.to_string(), "Carrier|0|");
+        assert_eq!(Ship::from_str(&ship.to_string()).unwrap(), ship);
+        assert!(Ship::from_str("Carrier|0|0,0;0,1").is_err());
+    }
+
+    #[test]
+    fn deserialize_ships_from_string() {
+        let ships = vec![Ship::submarine(), Ship{
+            class: ShipType::Destroyer,
+            locations: vec![Location{ row: 0, col: 1 }, Location{ row: 1, col: 1 }],
+            hits: 1
+        }];
+        let registry = ShipRegistry::standard();
+        assert_eq!(ships_from_str(&ships_to_string(&ships), &registry).unwrap(), ships);
+        assert!(ships_from_str("", &registry).unwrap().is_empty());
+    }
+}
@@ -1,36 +1,52 @@
+use std::str::FromStr;
+use crate::error::GameError;
 use crate::location::Location;
 
-#[derive(Eq, PartialEq, Clone)]
+#[derive(Eq, PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Shot {
     pub location: Location,
     pub hit: bool
 }
 
 impl Shot {
-    fn to_string(&self) -> String {
-        return format!("{}|{}",self.location.to_string(),self.hit);
+    #[allow(clippy::inherent_to_string)]
+    pub fn to_string(&self) -> String {
+        format!("{}|{}",self.location.to_string(),self.hit)
     }
+}
+
+impl FromStr for Shot {
+    type Err = GameError;
 
-    fn from_str(str: &str) -> Result<Self, String> {
-        let parts:Vec<String> = str.split("|").filter_map(|s| Option::from(String::from(s))).collect::<Vec<String>>();
+    fn from_str(str: &str) -> Result<Self, GameError> {
+        let parts:Vec<String> = str.split('|').map(String::from).collect::<Vec<String>>();
         if parts.len() != 2 {
-            return Err(format!("Expected to find 2 fields, found {}.", parts.len()));
+            return Err(GameError::parse(str, format!("Expected to find 2 fields, found {}.", parts.len())));
         }
         let location = Location::from_str(parts[0].as_str())?;
-        let hit = match parts[1].as_str() {
-            "true" => true,
-            "True" => true,
-            _ => false
-        };
+        let hit = matches!(parts[1].as_str(), "true" | "True");
+
+        Ok(Shot{location, hit})
+    }
+}
 
-        return Ok(Shot{location, hit})
+pub fn shots_to_string(shots: &[Shot]) -> String {
+    shots.iter().map(|s| s.to_string()).collect::<Vec<String>>().join("&")
+}
+
+pub fn shots_from_str(serialized: &str) -> Result<Vec<Shot>, GameError> {
+    if serialized.is_empty() {
+        return Ok(Vec::new());
     }
+    serialized.split('&').map(Shot::from_str).collect()
 }
 
 #[cfg(test)]
 mod tests {
+    use std::str::FromStr;
     use crate::location::Location;
-    use crate::shot::Shot;
+    use crate::shot::{Shot, shots_from_str, shots_to_string};
 
     #[test]
     fn shot_to_string() {
@@ -45,4 +61,17 @@ mod tests {
         assert!(Location{row:2,col:5}.eq(&shot.location));
         assert!(&shot.hit);
     }
+
+    #[test]
+    fn shots_to_string_and_back() {
+        let shots = vec![
+            Shot{ location: Location{row:0,col:1}, hit: true },
+            Shot{ location: Location{row:9,col:9}, hit: false },
+        ];
+        let str = shots_to_string(&shots);
+        assert_eq!(str, "1,0|true&9,9|false");
+        assert_eq!(shots_from_str(&str).unwrap(), shots);
+        assert!(shots_from_str("").unwrap().is_empty());
+        assert!(shots_from_str("1,0|true&oops").is_err());
+    }
 }
@@ -0,0 +1,229 @@
+//! Plays strategies against each other without any players attached, to compare how well they do.
+
+use std::fmt::Display;
+use std::thread;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use crate::ai;
+use crate::config::GameConfig;
+use crate::error::GameError;
+use crate::game::{GameResult, GameState};
+use crate::player::{PlayerTurn, PlayerType};
+
+/// Plays `games` games between two of the `ai::STRATEGIES`. Game `n` is seeded from `seed` and
+/// `n` alone, so the report is the same however many threads share the work. The strategies
+/// swap seats every game so neither always gets the first shot.
+pub struct Simulation {
+    pub config: GameConfig,
+    pub strategies: [String; 2],
+    pub games: u32,
+    pub seed: u64,
+    pub threads: usize,
+}
+
+impl Simulation {
+    pub fn new(first: &str, second: &str) -> Self {
+        Simulation {
+            config: GameConfig::default(),
+            strategies: [String::from(first), String::from(second)],
+            games: 100,
+            seed: 0,
+            threads: 1,
+        }
+    }
+
+    pub fn run(&self) -> Result<Report, GameError> {
+        self.config.validate()?;
+        if let Some(name) = self.strategies.iter().find(|name| ai::strategy(name, 0).is_none()) {
+            return Err(GameError::InvalidConfig { reason: format!("Unknown strategy {}, expected one of {}", name, ai::STRATEGIES.join(", ")) });
+        }
+        let threads = self.threads.clamp(1, self.games.max(1) as usize);
+        let outcomes: Vec<Result<Outcome, GameError>> = thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads)
+                .map(|worker| scope.spawn(move || {
+                    (worker as u32..self.games).step_by(threads).map(|game| self.play(game)).collect::<Vec<_>>()
+                }))
+                .collect();
+            let mut batches: Vec<_> = workers.into_iter().map(|worker| worker.join().expect("simulation thread panicked").into_iter()).collect();
+            // put the games back in order, worker `n` played games n, n + threads, ...
+            (0..self.games as usize).filter_map(|game| batches[game % threads].next()).collect()
+        });
+
+        let mut report = Report { strategies: self.strategies.clone(), games: self.games, wins: [0, 0], shots: [Vec::new(), Vec::new()] };
+        for outcome in outcomes {
+            let outcome = outcome?;
+            report.wins[outcome.winner] += 1;
+            report.shots[outcome.winner].push(outcome.shots);
+        }
+        Ok(report)
+    }
+
+    // plays game number `game`, the first strategy takes Player 1's seat in even games
+    fn play(&self, game: u32) -> Result<Outcome, GameError> {
+        let mut rng = StdRng::seed_from_u64(game_seed(self.seed, game));
+        let seats = if game.is_multiple_of(2) { [0, 1] } else { [1, 0] };
+        let mut strategies = seats.map(|index| ai::strategy(&self.strategies[index], rng.gen()).expect("strategies are checked before playing"));
+
+        let mut state = GameState::with_config(self.config.clone())?;
+        for player in [PlayerType::Player1, PlayerType::Player2] {
+            state = state.auto_place(player, &mut rng)?;
+        }
+        while state.result() == GameResult::InProgress {
+            let (player, seat) = match state.turn() {
+                PlayerTurn::Player1 => (PlayerType::Player1, 0),
+                _ => (PlayerType::Player2, 1),
+            };
+            let salvo = ai::next_salvo(strategies[seat].as_mut(), &state, player);
+            if salvo.is_empty() {
+                return Err(GameError::InvalidConfig { reason: format!("{} found nowhere left to fire", self.strategies[seats[seat]]) });
+            }
+            state = state.fire_salvo(player, &salvo)?;
+        }
+        let (winner, seat) = match state.result() {
+            GameResult::Player1Win => (PlayerType::Player1, 0),
+            _ => (PlayerType::Player2, 1),
+        };
+        Ok(Outcome { winner: seats[seat], shots: state.shots(&winner).len() as u32 })
+    }
+}
+
+// the seed for game number `game`, mixed so that neighbouring base seeds don't share games
+fn game_seed(seed: u64, game: u32) -> u64 {
+    splitmix64(splitmix64(seed) ^ game as u64)
+}
+
+// one step of the splitmix64 generator, which spreads nearby inputs across the whole range
+fn splitmix64(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+// who won one game, as an index into the strategies, and how many shots it took them
+struct Outcome {
+    winner: usize,
+    shots: u32,
+}
+
+/// The results of a `Simulation`.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Report {
+    pub strategies: [String; 2],
+    pub games: u32,
+    pub wins: [u32; 2],
+    /// The number of shots each strategy took in every game it won.
+    pub shots: [Vec<u32>; 2],
+}
+
+impl Report {
+    pub fn win_rate(&self, strategy: usize) -> f64 {
+        if self.games == 0 { 0.0 } else { self.wins[strategy] as f64 / self.games as f64 }
+    }
+
+    /// The average number of shots the strategy took to win, `None` if it never won.
+    pub fn average_shots(&self, strategy: usize) -> Option<f64> {
+        let shots = &self.shots[strategy];
+        if shots.is_empty() { None } else { Some(shots.iter().sum::<u32>() as f64 / shots.len() as f64) }
+    }
+
+    /// The smallest, median and largest number of shots the strategy took to win.
+    pub fn spread(&self, strategy: usize) -> Option<(u32, u32, u32)> {
+        let mut shots = self.shots[strategy].clone();
+        shots.sort_unstable();
+        Some((*shots.first()?, shots[shots.len() / 2], *shots.last()?))
+    }
+
+    /// How many games each strategy won in each band of `width` shots, starting from the band
+    /// holding the fewest shots any winner needed.
+    pub fn distribution(&self, width: u32) -> Vec<(u32, [u32; 2])> {
+        let width = width.max(1);
+        let all = self.shots.iter().flatten();
+        let (lowest, highest) = match (all.clone().min(), all.max()) {
+            (Some(lowest), Some(highest)) => (lowest / width * width, *highest),
+            _ => return Vec::new(),
+        };
+        let mut bands: Vec<(u32, [u32; 2])> = (lowest..=highest).step_by(width as usize).map(|band| (band, [0, 0])).collect();
+        for (strategy, shots) in self.shots.iter().enumerate() {
+            for shot in shots {
+                bands[((shot - lowest) / width) as usize].1[strategy] += 1;
+            }
+        }
+        bands
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> std::result::Result<(), ::std::fmt::Error> {
+        writeln!(f, "{} games of {} vs {}", self.games, self.strategies[0], self.strategies[1])?;
+        writeln!(f)?;
+        writeln!(f, "{:<12}{:>8}{:>10}{:>12}{:>8}{:>8}{:>8}", "strategy", "wins", "win rate", "avg shots", "min", "median", "max")?;
+        for (index, name) in self.strategies.iter().enumerate() {
+            let average = self.average_shots(index).map_or(String::from("-"), |average| format!("{:.1}", average));
+            let (min, median, max) = self.spread(index)
+                .map_or((String::from("-"), String::from("-"), String::from("-")), |(min, median, max)| (min.to_string(), median.to_string(), max.to_string()));
+            writeln!(f, "{:<12}{:>8}{:>9.1}%{:>12}{:>8}{:>8}{:>8}", name, self.wins[index], self.win_rate(index) * 100.0, average, min, median, max)?;
+        }
+        writeln!(f)?;
+        writeln!(f, "{:<12}{:>12}{:>12}", "shots to win", self.strategies[0], self.strategies[1])?;
+        for (band, wins) in self.distribution(10) {
+            writeln!(f, "{:<12}{:>12}{:>12}", format!("{}-{}", band, band + 9), wins[0], wins[1])?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::GameError;
+    use crate::simulation::{game_seed, Report, Simulation};
+
+    #[test]
+    fn same_seed_same_report_on_any_number_of_threads() {
+        let mut simulation = Simulation::new("hunt", "random");
+        simulation.games = 12;
+        simulation.seed = 99;
+        let report = simulation.run().unwrap();
+        assert_eq!(report.wins[0] + report.wins[1], 12);
+        assert_eq!(report.shots[0].len() as u32, report.wins[0]);
+        simulation.threads = 5;
+        assert_eq!(simulation.run().unwrap(), report);
+        simulation.seed = 100;
+        assert_ne!(simulation.run().unwrap(), report);
+    }
+
+    #[test]
+    fn neighbouring_seeds_play_different_games() {
+        let mut seeds: Vec<u64> = (0..2).flat_map(|seed| (0..1000).map(move |game| game_seed(seed, game))).collect();
+        assert_ne!(game_seed(7, 1), game_seed(8, 0));
+        seeds.sort_unstable();
+        seeds.dedup();
+        assert_eq!(seeds.len(), 2000);
+    }
+
+    #[test]
+    fn better_strategies_win_more() {
+        let mut simulation = Simulation::new("density", "random");
+        simulation.games = 20;
+        simulation.threads = 4;
+        let report = simulation.run().unwrap();
+        assert!(report.wins[0] > report.wins[1], "{}", report);
+        assert!(report.average_shots(0).unwrap() < 100.0);
+    }
+
+    #[test]
+    fn unknown_strategies_are_rejected() {
+        let result = Simulation::new("density", "psychic").run();
+        assert!(matches!(result, Err(GameError::InvalidConfig { .. })));
+    }
+
+    #[test]
+    fn distribution_bands() {
+        let report = Report { strategies: [String::from("a"), String::from("b")], games: 4, wins: [3, 1], shots: [vec![45, 52, 58], vec![71]] };
+        assert_eq!(report.distribution(10), vec![(40, [1, 0]), (50, [2, 0]), (60, [0, 0]), (70, [0, 1])]);
+        assert_eq!(report.spread(0), Some((45, 52, 58)));
+        assert_eq!(report.spread(1), Some((71, 71, 71)));
+        assert_eq!(report.win_rate(0), 0.75);
+        assert!(report.to_string().contains("a                  3     75.0%        51.7      45      52      58"));
+    }
+}
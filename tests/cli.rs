@@ -0,0 +1,135 @@
+use std::path::PathBuf;
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+// a fresh game file path for each test, so tests can run in parallel
+fn game_file(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("battleship-cli-{}-{}.game", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn battleship(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_battleship")).args(args).output().unwrap()
+}
+
+fn battleship_with_input(args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_battleship")).args(args)
+        .stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::piped())
+        .spawn().unwrap();
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+// the classic fleet, one ship per row
+const FLEET: [&str; 5] = ["Carrier", "Battleship", "Cruiser", "Submarine", "Destroyer"];
+
+#[test]
+fn play_a_game_through_the_file_commands() {
+    let path = game_file("play");
+    let game = path.to_str().unwrap();
+    assert_eq!(battleship(&["new", game, "--fleet", "classic"]).status.code(), Some(0));
+
+    for player in ["1", "2"] {
+        for (row, class) in FLEET.iter().enumerate() {
+            let location = format!("0,{}", row);
+            let output = battleship(&["place", game, player, class, &location, "right"]);
+            assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stderr));
+        }
+    }
+
+    let output = battleship(&["fire", game, "1", "0,0"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "Player 1 fires at (0, 0) and hits Player 2's ship!\n");
+
+    let output = battleship(&["show", game, "2"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(stdout(&output).starts_with("In Progress - Player 2 to fire"));
+    assert!(stdout(&output).contains("  0  X  #  #  #  #"));
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn errors_map_to_exit_codes() {
+    let path = game_file("errors");
+    let game = path.to_str().unwrap();
+    assert_eq!(battleship(&["show", game, "1"]).status.code(), Some(2));
+    assert_eq!(battleship(&["new", game]).status.code(), Some(0));
+
+    assert_eq!(battleship(&["fire", game, "1", "0,0"]).status.code(), Some(11));
+    assert_eq!(battleship(&["place", game, "1", "Carrier", "8,0", "right"]).status.code(), Some(13));
+    assert_eq!(battleship(&["place", game, "1", "Carrier", "0,0", "right"]).status.code(), Some(0));
+    assert_eq!(battleship(&["place", game, "1", "Submarine", "1,0", "down"]).status.code(), Some(14));
+    assert_eq!(battleship(&["remove", game, "1", "Battleship", "0,0"]).status.code(), Some(17));
+    assert_eq!(battleship(&["place", game, "3", "Carrier", "0,0", "right"]).status.code(), Some(1));
+    assert_eq!(battleship(&["new", game, "--grid", "0x0"]).status.code(), Some(19));
+
+    std::fs::write(&path, "not a game").unwrap();
+    assert_eq!(battleship(&["show", game, "1"]).status.code(), Some(20));
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn replay_a_saved_game() {
+    let path = game_file("replay");
+    let game = path.to_str().unwrap();
+    assert_eq!(battleship(&["new", game, "--fleet", "classic"]).status.code(), Some(0));
+    for player in ["1", "2"] {
+        for (row, class) in FLEET.iter().enumerate() {
+            battleship(&["place", game, player, class, &format!("0,{}", row), "right"]);
+        }
+    }
+    battleship(&["fire", game, "1", "9,9"]);
+    battleship(&["fire", game, "2", "0,0"]);
+
+    let output = battleship_with_input(&["replay", game], "next\njump 2\nquit\n");
+    assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stderr));
+    let shown = stdout(&output);
+    assert!(shown.contains("Move 0 of 2"));
+    assert!(shown.contains("Player 1 fires at (9, 9) and misses!"));
+    assert!(shown.contains("Move 2 of 2"));
+    assert_eq!(battleship(&["replay"]).status.code(), Some(1));
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn fire_a_salvo_through_the_file_commands() {
+    let path = game_file("salvo");
+    let game = path.to_str().unwrap();
+    assert_eq!(battleship(&["new", game, "--fleet", "classic", "--salvo", "ships"]).status.code(), Some(0));
+    for player in ["1", "2"] {
+        for (row, class) in FLEET.iter().enumerate() {
+            let location = format!("0,{}", row);
+            assert_eq!(battleship(&["place", game, player, class, &location, "right"]).status.code(), Some(0));
+        }
+    }
+
+    assert_eq!(battleship(&["fire", game, "1", "0,0"]).status.code(), Some(23));
+    let output = battleship(&["fire", game, "1", "0,0", "1,0", "9,9", "9,8", "9,7"]);
+    assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(stdout(&output).ends_with("Player 1 fires at (9, 7) and misses!\n"));
+    assert!(stdout(&battleship(&["show", game, "2"])).starts_with("In Progress - Player 2 to fire"));
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn off_grid_files_are_an_error_not_a_crash() {
+    let path = game_file("off-grid");
+    let game = path.to_str().unwrap();
+    assert_eq!(battleship(&["new", game, "--fleet", "classic"]).status.code(), Some(0));
+    assert_eq!(battleship(&["place", game, "1", "Destroyer", "0,0", "right"]).status.code(), Some(0));
+    let saved = std::fs::read_to_string(&path).unwrap();
+    assert!(saved.contains("Destroyer|0|0,0;1,0"));
+    std::fs::write(&path, saved.replace("Destroyer|0|0,0;1,0", "Destroyer|0|50,50;51,50")).unwrap();
+
+    let output = battleship(&["show", game, "1"]);
+    assert_eq!(output.status.code(), Some(20));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("off the 10x10 grid"), "{}", stderr);
+    assert!(!stderr.contains("panicked"), "{}", stderr);
+    std::fs::remove_file(path).unwrap();
+}
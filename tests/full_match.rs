@@ -0,0 +1,81 @@
+use battleship::{Direction, GameError, GameResult, GameState, Location, PlayerTurn, PlayerType, ShipType};
+
+// places every ship in the player's fleet on its own row, starting at column 0
+fn place_fleet(state: GameState, player: PlayerType) -> GameState {
+    let classes: Vec<ShipType> = state.ships(&player).iter().map(|ship| ship.class.clone()).collect();
+    let mut state = state;
+    for (row, class) in classes.into_iter().enumerate() {
+        state = state.place(player, class, Location { row: row as u32, col: 0 }, Direction::Right).unwrap();
+    }
+    state
+}
+
+fn fleet_locations(state: &GameState, player: PlayerType) -> Vec<Location> {
+    state.ships(&player).iter().flat_map(|ship| ship.locations.clone()).collect()
+}
+
+#[test]
+fn setup_transitions_to_in_progress() {
+    let state = GameState::new();
+    assert_eq!(state.result(), GameResult::InSetup);
+
+    let state = place_fleet(state, PlayerType::Player1);
+    assert!(state.ready(PlayerType::Player1));
+    assert!(!state.ready(PlayerType::Player2));
+    assert_eq!(state.result(), GameResult::InSetup);
+
+    let state = place_fleet(state, PlayerType::Player2);
+    assert_eq!(state.result(), GameResult::InProgress);
+    assert_eq!(state.turn(), &PlayerTurn::Player1);
+}
+
+#[test]
+fn ships_can_be_removed_and_placed_again_during_setup() {
+    let state = GameState::new();
+    let state = state.place(PlayerType::Player1, ShipType::Submarine, Location { row: 0, col: 0 }, Direction::Down).unwrap();
+    let state = state.remove(PlayerType::Player1, ShipType::Submarine, Location { row: 1, col: 0 }).unwrap();
+    assert!(state.ships(&PlayerType::Player1).iter().all(|ship| ship.locations.is_empty()));
+    assert!(state.place(PlayerType::Player1, ShipType::Submarine, Location { row: 0, col: 0 }, Direction::Down).is_ok());
+}
+
+#[test]
+fn play_full_match_until_player_1_wins() {
+    let state = place_fleet(GameState::new(), PlayerType::Player1);
+    let mut state = place_fleet(state, PlayerType::Player2);
+
+    let targets = fleet_locations(&state, PlayerType::Player2);
+    // the fleets only cover rows 0-6, so player 2 misses everything below them
+    let mut misses = (7..10).flat_map(|row| (0..10).map(move |col| Location { row, col }));
+
+    for (i, target) in targets.iter().enumerate() {
+        state = state.fire(PlayerType::Player1, target).unwrap();
+        if i + 1 == targets.len() {
+            break;
+        }
+        assert_eq!(state.turn(), &PlayerTurn::Player2);
+        state = state.fire(PlayerType::Player2, &misses.next().unwrap()).unwrap();
+    }
+
+    assert_eq!(state.result(), GameResult::Player1Win);
+    assert_eq!(state.turn(), &PlayerTurn::Neither);
+    assert!(state.ships(&PlayerType::Player2).iter().all(|ship| ship.sunk()));
+    assert_eq!(state.last_message().unwrap(), "Game over. Player 1 wins!");
+    assert!(matches!(
+        state.fire(PlayerType::Player2, &Location { row: 5, col: 5 }),
+        Err(GameError::WrongPhase { actual: GameResult::Player1Win, .. })
+    ));
+}
+
+#[test]
+fn actions_out_of_turn_are_rejected() {
+    let state = place_fleet(GameState::new(), PlayerType::Player1);
+    let state = place_fleet(state, PlayerType::Player2);
+    assert_eq!(
+        state.fire(PlayerType::Player2, &Location { row: 0, col: 0 }).err(),
+        Some(GameError::NotYourTurn { player: PlayerType::Player2 })
+    );
+    assert!(matches!(
+        state.place(PlayerType::Player1, ShipType::Submarine, Location { row: 9, col: 0 }, Direction::Right),
+        Err(GameError::WrongPhase { expected: GameResult::InSetup, .. })
+    ));
+}
@@ -0,0 +1,64 @@
+#![cfg(feature = "serde")]
+
+use std::fs;
+use std::path::PathBuf;
+use battleship::{
+    Direction, FleetSpec, GameConfig, GameResult, GameState, Location, PlayerTurn, PlayerType, Ship, ShipClass,
+    ShipRegistry, ShipType, Shot,
+};
+
+// compares against tests/golden/<name>, run with UPDATE_GOLDEN=1 to rewrite the files
+fn assert_golden(name: &str, json: &str) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden").join(name);
+    if std::env::var("UPDATE_GOLDEN").is_ok() {
+        fs::write(&path, format!("{}\n", json)).unwrap();
+    }
+    let golden = fs::read_to_string(&path).unwrap();
+    assert_eq!(json, golden.trim_end(), "{} does not match", name);
+}
+
+fn game_in_progress() -> GameState {
+    let mut registry = ShipRegistry::standard();
+    let patrol_boat = registry.register(ShipClass::new("Patrol Boat", 1)).unwrap();
+    let config = GameConfig {
+        registry,
+        fleet: FleetSpec::new(vec![(ShipType::Destroyer, 1), (patrol_boat.clone(), 1)]),
+        ..GameConfig::with_grid(5, 5)
+    };
+    let state = GameState::with_config(config).unwrap();
+    let state = state.place(PlayerType::Player1, ShipType::Destroyer, Location { row: 0, col: 0 }, Direction::Right).unwrap();
+    let state = state.place(PlayerType::Player1, patrol_boat.clone(), Location { row: 4, col: 4 }, Direction::Up).unwrap();
+    let state = state.place(PlayerType::Player2, ShipType::Destroyer, Location { row: 1, col: 1 }, Direction::Down).unwrap();
+    let state = state.place(PlayerType::Player2, patrol_boat, Location { row: 3, col: 3 }, Direction::Left).unwrap();
+    let state = state.fire(PlayerType::Player1, &Location { row: 1, col: 1 }).unwrap();
+    state.fire(PlayerType::Player2, &Location { row: 2, col: 2 }).unwrap()
+}
+
+#[test]
+fn new_game_matches_golden_file() {
+    let state = GameState::new();
+    let json = serde_json::to_string_pretty(&state).unwrap();
+    assert_golden("new_game.json", &json);
+    assert_eq!(serde_json::from_str::<GameState>(&json).unwrap(), state);
+}
+
+#[test]
+fn game_in_progress_matches_golden_file() {
+    let state = game_in_progress();
+    assert_eq!(state.result(), GameResult::InProgress);
+    let json = serde_json::to_string_pretty(&state).unwrap();
+    assert_golden("game_in_progress.json", &json);
+    assert_eq!(serde_json::from_str::<GameState>(&json).unwrap(), state);
+}
+
+#[test]
+fn value_types_are_stable() {
+    assert_eq!(serde_json::to_string(&Location { row: 2, col: 3 }).unwrap(), r#"{"row":2,"col":3}"#);
+    assert_eq!(serde_json::to_string(&Shot { location: Location { row: 0, col: 1 }, hit: true }).unwrap(), r#"{"location":{"row":0,"col":1},"hit":true}"#);
+    assert_eq!(serde_json::to_string(&ShipType::TorpedoBoat).unwrap(), r#""TorpedoBoat""#);
+    assert_eq!(serde_json::to_string(&ShipType::Custom(ShipClass::new("Dreadnought", 6))).unwrap(), r#"{"Custom":{"name":"Dreadnought","length":6,"shape":null}}"#);
+    assert_eq!(serde_json::to_string(&Ship::destroyer()).unwrap(), r#"{"class":"Destroyer","locations":[],"hits":0}"#);
+    assert_eq!(serde_json::to_string(&PlayerType::Player2).unwrap(), r#""Player2""#);
+    assert_eq!(serde_json::to_string(&PlayerTurn::Either).unwrap(), r#""Either""#);
+    assert_eq!(serde_json::to_string(&GameResult::Player1Win).unwrap(), r#""Player1Win""#);
+}
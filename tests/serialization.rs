@@ -0,0 +1,121 @@
+use std::str::FromStr;
+use battleship::{
+    Direction, FirstShooter, FleetSpec, GameConfig, GameState, Location, PlayerTurn, PlayerType, ShipClass,
+    ShipRegistry,
+};
+use proptest::prelude::*;
+
+const DIRECTIONS: [Direction; 4] = [Direction::Up, Direction::Down, Direction::Left, Direction::Right];
+
+fn config(width: u32, height: u32, fleet: u8, first_shooter: u8, custom: bool) -> GameConfig {
+    let mut registry = ShipRegistry::standard();
+    let mut fleet = match fleet {
+        0 => FleetSpec::classic(),
+        1 => FleetSpec::original(),
+        _ => FleetSpec::russian(),
+    };
+    if custom {
+        let hook = ShipClass::shaped("Hook", vec![Location { row: 0, col: 0 }, Location { row: 1, col: 0 }, Location { row: 1, col: 1 }]);
+        fleet.ships.push((registry.register(hook).unwrap(), 1));
+    }
+    let first_shooter = match first_shooter {
+        0 => FirstShooter::Player1,
+        1 => FirstShooter::Random,
+        _ => FirstShooter::LoserOfLastGame,
+    };
+    GameConfig { first_shooter, width, height, registry, fleet, ..GameConfig::default() }
+}
+
+// places whatever ships are still unplaced at the first spot they fit
+fn complete_fleet(state: GameState, player: PlayerType) -> GameState {
+    let mut state = state;
+    let unplaced: Vec<_> = state.ships(&player).iter().filter(|ship| ship.locations.is_empty()).map(|ship| ship.class.clone()).collect();
+    for class in unplaced {
+        'search: for row in 0..state.height() {
+            for col in 0..state.width() {
+                for direction in DIRECTIONS {
+                    if let Ok(next) = state.place(player, class.clone(), Location { row, col }, direction) {
+                        state = next;
+                        break 'search;
+                    }
+                }
+            }
+        }
+    }
+    state
+}
+
+fn player(index: u8) -> PlayerType {
+    if index == 0 { PlayerType::Player1 } else { PlayerType::Player2 }
+}
+
+prop_compose! {
+    fn game_state()(
+        width in 5u32..16,
+        height in 5u32..16,
+        fleet in 0u8..3,
+        first_shooter in 0u8..3,
+        custom in any::<bool>(),
+        placements in prop::collection::vec((0u8..2, 0u32..16, 0u32..16, 0usize..4, 0usize..8), 0..20),
+        complete in any::<bool>(),
+        shots in prop::collection::vec((0u32..16, 0u32..16), 0..150),
+    ) -> GameState {
+        let mut state = match GameState::with_config(config(width, height, fleet, first_shooter, custom)) {
+            Ok(state) => state,
+            Err(_) => GameState::new(),
+        };
+        for (index, row, col, direction, ship) in placements {
+            let ships = state.ships(&player(index));
+            let class = ships[ship % ships.len()].class.clone();
+            if let Ok(next) = state.place(player(index), class, Location { row, col }, DIRECTIONS[direction]) {
+                state = next;
+            }
+        }
+        if complete {
+            state = complete_fleet(state, PlayerType::Player1);
+            state = complete_fleet(state, PlayerType::Player2);
+        }
+        for (row, col) in shots {
+            let shooter = match state.turn() {
+                PlayerTurn::Player1 => PlayerType::Player1,
+                PlayerTurn::Player2 => PlayerType::Player2,
+                _ => break,
+            };
+            if let Ok(next) = state.fire(shooter, &Location { row, col }) {
+                state = next;
+            }
+        }
+        state
+    }
+}
+
+proptest! {
+    #[test]
+    fn decode_encode_round_trips(state in game_state()) {
+        let encoded = state.to_string();
+        let decoded = GameState::from_str(&encoded);
+        prop_assert_eq!(decoded, Ok(state));
+    }
+
+    #[test]
+    fn rematch_round_trips(state in game_state()) {
+        let rematch = state.rematch();
+        prop_assert_eq!(GameState::from_str(&rematch.to_string()), Ok(rematch));
+    }
+
+    #[test]
+    fn events_rebuild_the_game(state in game_state()) {
+        prop_assert_eq!(GameState::from_events(state.events()), Ok(state.clone()));
+        let rematch = state.rematch();
+        prop_assert_eq!(GameState::from_events(rematch.events()), Ok(rematch));
+    }
+}
+
+#[test]
+fn decoding_rejects_garbage() {
+    assert!(GameState::from_str("").is_err());
+    assert!(GameState::from_str("hello world").is_err());
+    let encoded = GameState::new().to_string();
+    assert!(GameState::from_str(&encoded.replace("battleship|2", "battleship|99")).is_err());
+    assert!(GameState::from_str(&encoded.replace("p2_shots|", "p3_shots|")).is_err());
+}
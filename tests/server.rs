@@ -0,0 +1,228 @@
+#![cfg(feature = "server")]
+
+use std::io::{BufRead, BufReader};
+use std::thread;
+use serde_json::{json, Value};
+use battleship::server::Server;
+
+// starts a server on a free local port and returns its base url
+fn start() -> String {
+    let server = Server::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", server.addr());
+    thread::spawn(move || server.run());
+    url
+}
+
+// POSTs `body` and returns the status code and JSON reply, including error replies
+fn post(url: &str, body: Value) -> (u16, Value) {
+    reply(ureq::post(url).send_json(body))
+}
+
+fn post_as(token: &str, url: &str, body: Value) -> (u16, Value) {
+    reply(ureq::post(url).set("Authorization", &format!("Bearer {}", token)).send_json(body))
+}
+
+fn get_as(token: &str, url: &str) -> (u16, Value) {
+    reply(ureq::get(url).set("Authorization", &format!("Bearer {}", token)).call())
+}
+
+fn reply(result: Result<ureq::Response, ureq::Error>) -> (u16, Value) {
+    match result {
+        Ok(response) => (response.status(), response.into_json().unwrap()),
+        Err(ureq::Error::Status(status, response)) => (status, response.into_json().unwrap()),
+        Err(error) => panic!("{}", error),
+    }
+}
+
+// reads a server-sent event stream until the server closes it, returning (id, event, data)
+fn read_events(response: ureq::Response) -> Vec<(usize, String, Value)> {
+    let mut events = Vec::new();
+    let (mut id, mut name) = (0, String::new());
+    for line in BufReader::new(response.into_reader()).lines() {
+        let line = line.unwrap();
+        if let Some(value) = line.strip_prefix("id: ") {
+            id = value.parse().unwrap();
+        } else if let Some(value) = line.strip_prefix("event: ") {
+            name = value.to_string();
+        } else if let Some(value) = line.strip_prefix("data: ") {
+            events.push((id, name.clone(), serde_json::from_str(value).unwrap()));
+        }
+    }
+    events
+}
+
+fn new_game(url: &str) -> String {
+    let (status, body) = post(&format!("{}/games", url), json!({"width": 5, "height": 5, "fleet": "Destroyer:1"}));
+    assert_eq!(status, 201);
+    body["id"].as_str().unwrap().to_string()
+}
+
+fn join(game: &str, player: &str) -> String {
+    let (status, body) = post(&format!("{}/join", game), json!({"player": player}));
+    assert_eq!((status, body["player"].as_str()), (200, Some(player)));
+    body["token"].as_str().unwrap().to_string()
+}
+
+#[test]
+fn play_a_game_over_http() {
+    let url = start();
+    let game = format!("{}/games/{}", url, new_game(&url));
+
+    let (_, joined) = post(&format!("{}/join", game), json!({}));
+    assert_eq!(joined["player"], "Player1");
+    let token1 = joined["token"].as_str().unwrap().to_string();
+    let (_, joined) = post(&format!("{}/join", game), json!({}));
+    assert_eq!(joined["player"], "Player2");
+    let token2 = joined["token"].as_str().unwrap().to_string();
+
+    for (player, token) in [(1, &token1), (2, &token2)] {
+        let (status, view) = post_as(
+            token,
+            &format!("{}/players/{}/place", game, player),
+            json!({"class": "Destroyer", "location": {"row": 0, "col": 0}, "direction": "Right"}),
+        );
+        assert_eq!(status, 200);
+        assert_eq!(view["own_board"][0], json!(["Ship", "Ship", "Empty", "Empty", "Empty"]));
+    }
+
+    let (_, view) = post_as(&token1, &format!("{}/players/1/fire", game), json!({"location": {"row": 0, "col": 0}}));
+    assert_eq!(view["enemy_board"][0][0], "Hit");
+    assert_eq!(view["turn"], "Player2");
+    post_as(&token2, &format!("{}/players/2/fire", game), json!({"location": {"row": 4, "col": 4}}));
+    let (_, view) = post_as(&token1, &format!("{}/players/1/fire", game), json!({"location": {"row": 0, "col": 1}}));
+    assert_eq!(view["result"], "Player1Win");
+
+    let (status, view) = get_as(&token2, &format!("{}/players/2", game));
+    assert_eq!(status, 200);
+    assert_eq!(view["own_board"][0], json!(["ShipHit", "ShipHit", "Empty", "Empty", "Empty"]));
+    assert_eq!(view["messages"].as_array().unwrap().last().unwrap(), "Game over. Player 1 wins!");
+}
+
+#[test]
+fn only_sunk_enemy_ships_are_revealed() {
+    let url = start();
+    let game = format!("{}/games/{}", url, new_game(&url));
+    let tokens = [join(&game, "Player1"), join(&game, "Player2")];
+    let place = |row, direction| json!({"class": "Destroyer", "location": {"row": row, "col": 2}, "direction": direction});
+    post_as(&tokens[0], &format!("{}/players/1/place", game), place(0, "Right"));
+    post_as(&tokens[1], &format!("{}/players/2/place", game), place(2, "Down"));
+
+    let (_, view) = post_as(&tokens[0], &format!("{}/players/1/fire", game), json!({"location": {"row": 2, "col": 2}}));
+    assert_eq!(view["enemy_ships"], json!([]));
+    assert!(!view.to_string().contains(r#"{"col":2,"row":3}"#));
+
+    post_as(&tokens[1], &format!("{}/players/2/fire", game), json!({"location": {"row": 4, "col": 4}}));
+    let (_, view) = post_as(&tokens[0], &format!("{}/players/1/fire", game), json!({"location": {"row": 3, "col": 2}}));
+    assert_eq!(view["enemy_ships"][0]["locations"], json!([{"row": 2, "col": 2}, {"row": 3, "col": 2}]));
+}
+
+#[test]
+fn actions_need_the_seat_token() {
+    let url = start();
+    let game = format!("{}/games/{}", url, new_game(&url));
+
+    let (status, body) = get_as("", &format!("{}/players/1", game));
+    assert_eq!((status, body["error"].as_str()), (403, Some("Forbidden")));
+
+    let token1 = join(&game, "Player1");
+    let token2 = join(&game, "Player2");
+    let (status, body) = reply(ureq::get(&format!("{}/players/1", game)).call());
+    assert_eq!((status, body["error"].as_str()), (401, Some("Unauthorized")));
+
+    let (status, body) = get_as(&token2, &format!("{}/players/1", game));
+    assert_eq!((status, body["error"].as_str()), (403, Some("Forbidden")));
+
+    let place = json!({"class": "Destroyer", "location": {"row": 0, "col": 0}, "direction": "Right"});
+    let (status, _) = post_as(&token2, &format!("{}/players/1/place", game), place.clone());
+    assert_eq!(status, 403);
+    let (status, _) = post_as(&token1, &format!("{}/players/1/place", game), place);
+    assert_eq!(status, 200);
+}
+
+#[test]
+fn errors_are_reported_as_json() {
+    let url = start();
+    let game = format!("{}/games/{}", url, new_game(&url));
+
+    let (status, body) = get_as("", &format!("{}/games/missing/players/1", url));
+    assert_eq!((status, body["error"].as_str()), (404, Some("NotFound")));
+
+    let token = join(&game, "Player1");
+    let (status, body) = post(&format!("{}/join", game), json!({"player": "Player1"}));
+    assert_eq!((status, body["error"].as_str()), (409, Some("Conflict")));
+
+    let (status, body) = post_as(&token, &format!("{}/players/1/fire", game), json!({"location": {"row": 0, "col": 0}}));
+    assert_eq!((status, body["error"].as_str()), (409, Some("WrongPhase")));
+
+    let (status, body) = post_as(
+        &token,
+        &format!("{}/players/1/place", game),
+        json!({"class": "Destroyer", "location": {"row": 0, "col": 4}, "direction": "Right"}),
+    );
+    assert_eq!((status, body["error"].as_str()), (422, Some("NoRoom")));
+
+    let (status, body) = post_as(&token, &format!("{}/players/1/place", game), json!({"class": "Destroyer"}));
+    assert_eq!((status, body["error"].as_str()), (400, Some("BadRequest")));
+
+    let (status, body) = post(&format!("{}/games", url), json!({"width": 0}));
+    assert_eq!((status, body["error"].as_str()), (400, Some("InvalidConfig")));
+}
+
+#[test]
+fn oversized_games_are_rejected() {
+    let url = start();
+    let (status, body) = post(&format!("{}/games", url), json!({"width": 70000, "height": 70000}));
+    assert_eq!((status, body["error"].as_str()), (400, Some("InvalidConfig")));
+    let (status, body) = post(&format!("{}/games", url), json!({"fleet": "Torpedo Boat:4294967295;Destroyer:4294967295"}));
+    assert_eq!((status, body["error"].as_str()), (400, Some("InvalidConfig")));
+    let (status, _) = post(&format!("{}/games", url), json!({"width": 100, "height": 100}));
+    assert_eq!(status, 201);
+}
+
+#[test]
+fn events_are_streamed_as_they_happen() {
+    let url = start();
+    let game = format!("{}/games/{}", url, new_game(&url));
+    let tokens = [join(&game, "Player1"), join(&game, "Player2")];
+
+    let response = ureq::get(&format!("{}/players/2/events?token={}", game, tokens[1])).call().unwrap();
+    assert_eq!(response.header("Content-Type"), Some("text/event-stream"));
+    let stream = thread::spawn(move || read_events(response));
+
+    let place = |row| json!({"class": "Destroyer", "location": {"row": row, "col": 0}, "direction": "Right"});
+    post_as(&tokens[0], &format!("{}/players/1/place", game), place(0));
+    post_as(&tokens[1], &format!("{}/players/2/place", game), place(1));
+    post_as(&tokens[0], &format!("{}/players/1/fire", game), json!({"location": {"row": 1, "col": 0}}));
+    post_as(&tokens[1], &format!("{}/players/2/fire", game), json!({"location": {"row": 4, "col": 4}}));
+    post_as(&tokens[0], &format!("{}/players/1/fire", game), json!({"location": {"row": 1, "col": 1}}));
+
+    // the stream ends on its own once the game is over
+    let events = stream.join().unwrap();
+    let names: Vec<&str> = events.iter().map(|(_, name, _)| name.as_str()).collect();
+    assert_eq!(names, [
+        "ShipPlaced",
+        "ShipPlaced", "TurnChanged", "Message",
+        "ShotFired", "TurnChanged", "Message",
+        "ShotFired", "TurnChanged", "Message",
+        "ShotFired", "TurnChanged", "GameOver", "Message", "Message",
+    ]);
+    assert!(events.iter().enumerate().all(|(index, (id, _, _))| index == *id));
+    assert_eq!(events[0].2, json!({"type": "ShipPlaced", "player": "Player1", "class": "Destroyer"}));
+    assert_eq!(events[1].2["location"], json!({"row": 1, "col": 0}));
+    assert_eq!(events[4].2, json!({"type": "ShotFired", "player": "Player1", "location": {"row": 1, "col": 0}, "hit": true, "sunk": null}));
+    assert_eq!(events[10].2["sunk"], "Destroyer");
+    assert_eq!(events[12].2, json!({"type": "GameOver", "winner": "Player1"}));
+    assert_eq!(events[14].2["text"], "Game over. Player 1 wins!");
+
+    // reconnecting picks up after the last event the client saw
+    let response = ureq::get(&format!("{}/players/1/events", game))
+        .set("Authorization", &format!("Bearer {}", tokens[0]))
+        .set("Last-Event-ID", "11")
+        .call()
+        .unwrap();
+    let events = read_events(response);
+    assert_eq!(events.iter().map(|(id, _, _)| *id).collect::<Vec<usize>>(), [12, 13, 14]);
+
+    let (status, _) = reply(ureq::get(&format!("{}/players/1/events", game)).call());
+    assert_eq!(status, 401);
+}
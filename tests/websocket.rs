@@ -0,0 +1,126 @@
+#![cfg(feature = "server")]
+
+use std::net::TcpStream;
+use std::thread;
+use std::time::Duration;
+use serde_json::{json, Value};
+use tungstenite::client::IntoClientRequest;
+use tungstenite::stream::MaybeTlsStream;
+use tungstenite::{Message, WebSocket};
+use battleship::server::Server;
+
+type Socket = WebSocket<MaybeTlsStream<TcpStream>>;
+
+// starts a server and returns the base urls for HTTP and WebSockets
+fn start() -> (String, String) {
+    let server = Server::bind("127.0.0.1:0").unwrap();
+    let ws = server.listen_websockets("127.0.0.1:0").unwrap();
+    let http = format!("http://{}", server.addr());
+    thread::spawn(move || server.run());
+    (http, format!("ws://{}", ws))
+}
+
+// creates a 5x5 game with one Destroyer each and joins both seats, returning the id and tokens
+fn new_game(http: &str) -> (String, [String; 2]) {
+    let game: Value = ureq::post(&format!("{}/games", http))
+        .send_json(json!({"width": 5, "height": 5, "fleet": "Destroyer:1"}))
+        .unwrap()
+        .into_json()
+        .unwrap();
+    let id = game["id"].as_str().unwrap().to_string();
+    let join = |player: &str| -> String {
+        let joined: Value = ureq::post(&format!("{}/games/{}/join", http, id))
+            .send_json(json!({"player": player}))
+            .unwrap()
+            .into_json()
+            .unwrap();
+        joined["token"].as_str().unwrap().to_string()
+    };
+    let tokens = [join("Player1"), join("Player2")];
+    (id, tokens)
+}
+
+// connects with the given subprotocol, or returns the status code the handshake failed with
+fn connect(url: &str, protocol: &str) -> Result<Socket, u16> {
+    let mut request = url.into_client_request().unwrap();
+    request.headers_mut().insert("Sec-WebSocket-Protocol", protocol.parse().unwrap());
+    let (socket, response) = match tungstenite::connect(request) {
+        Ok(connected) => connected,
+        Err(tungstenite::Error::Http(response)) => return Err(response.status().as_u16()),
+        Err(error) => panic!("{}", error),
+    };
+    assert_eq!(response.headers()["Sec-WebSocket-Protocol"], protocol);
+    if let MaybeTlsStream::Plain(stream) = socket.get_ref() {
+        stream.set_read_timeout(Some(Duration::from_secs(10))).unwrap();
+    }
+    Ok(socket)
+}
+
+fn receive(socket: &mut Socket) -> Value {
+    loop {
+        if let Message::Text(text) = socket.read().unwrap() {
+            return serde_json::from_str(&text).unwrap();
+        }
+    }
+}
+
+fn send(socket: &mut Socket, command: Value) {
+    socket.send(Message::Text(command.to_string())).unwrap();
+}
+
+#[test]
+fn play_over_websockets() {
+    let (http, ws) = start();
+    let (id, tokens) = new_game(&http);
+    let mut p1 = connect(&format!("{}/games/{}/players/1?token={}", ws, id, tokens[0]), "battleship.v1").unwrap();
+    let mut p2 = connect(&format!("{}/games/{}/players/2?token={}", ws, id, tokens[1]), "battleship.v1").unwrap();
+
+    let hello = receive(&mut p1);
+    assert_eq!((&hello["type"], &hello["version"]), (&json!("Hello"), &json!(1)));
+    assert_eq!(hello["view"]["player"], "Player1");
+    assert_eq!(receive(&mut p2)["view"]["player"], "Player2");
+
+    send(&mut p1, json!({"type": "Place", "class": "Destroyer", "location": {"row": 0, "col": 0}, "direction": "Right"}));
+    assert_eq!(receive(&mut p1), json!({"type": "Event", "id": 0, "event": {
+        "type": "ShipPlaced", "player": "Player1", "class": "Destroyer", "location": {"row": 0, "col": 0}, "direction": "Right",
+    }}));
+    assert_eq!(receive(&mut p1), json!({"type": "Diff", "result": "InSetup", "turn": "Either", "enemy_board": [], "own_board": [
+        {"row": 0, "col": 0, "cell": "Ship"}, {"row": 0, "col": 1, "cell": "Ship"},
+    ]}));
+    assert_eq!(receive(&mut p2)["event"], json!({"type": "ShipPlaced", "player": "Player1", "class": "Destroyer"}));
+
+    send(&mut p2, json!({"type": "Place", "class": "Destroyer", "location": {"row": 1, "col": 0}, "direction": "Right"}));
+    let names: Vec<Value> = (0..3).map(|_| receive(&mut p1)["event"]["type"].clone()).collect();
+    assert_eq!(names, [json!("ShipPlaced"), json!("TurnChanged"), json!("Message")]);
+    assert_eq!(receive(&mut p1)["turn"], "Player1");
+
+    send(&mut p1, json!({"type": "Fire", "location": {"row": 1, "col": 0}}));
+    let shot = receive(&mut p1);
+    assert_eq!(shot["event"], json!({"type": "ShotFired", "player": "Player1", "location": {"row": 1, "col": 0}, "hit": true, "sunk": null}));
+}
+
+#[test]
+fn rejected_commands_reply_with_errors() {
+    let (http, ws) = start();
+    let (id, tokens) = new_game(&http);
+    let mut p1 = connect(&format!("{}/games/{}/players/1?token={}", ws, id, tokens[0]), "battleship.v1").unwrap();
+    receive(&mut p1);
+
+    send(&mut p1, json!({"type": "Fire", "location": {"row": 1, "col": 0}}));
+    assert_eq!(receive(&mut p1)["error"], "WrongPhase");
+    send(&mut p1, json!({"type": "Launch"}));
+    let error = receive(&mut p1);
+    assert_eq!((&error["type"], &error["error"]), (&json!("Error"), &json!("BadRequest")));
+}
+
+#[test]
+fn handshake_checks_the_token_and_protocol() {
+    let (http, ws) = start();
+    let (id, tokens) = new_game(&http);
+
+    let rejected = |url: String, protocol: &str| connect(&url, protocol).err();
+    assert_eq!(rejected(format!("{}/games/{}/players/1?token={}", ws, id, tokens[1]), "battleship.v1"), Some(403));
+    assert_eq!(rejected(format!("{}/games/{}/players/1", ws, id), "battleship.v1"), Some(401));
+    assert_eq!(rejected(format!("{}/games/missing/players/1?token={}", ws, tokens[0]), "battleship.v1"), Some(404));
+    assert_eq!(rejected(format!("{}/games/{}/players/1?token={}", ws, id, tokens[0]), "battleship.v2"), Some(400));
+}